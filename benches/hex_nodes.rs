@@ -0,0 +1,51 @@
+//! Nodes and memory of the search tree for 11 x 11 Hex, creating children lazily on first
+//! selection, measured, versus creating a child for every action when a node is expanded,
+//! estimated from the edges of the same tree since eager expansion is no longer implemented
+
+#[allow(dead_code)]
+#[path = "../examples/hex/env.rs"]
+mod env;
+
+use env::Hex;
+use search_rl::mcts::config::SearchConfig;
+use search_rl::mcts::evaluator::UniformEvaluator;
+use search_rl::mcts::{Node, Stats, Tree};
+use std::mem::size_of;
+use std::time::Instant;
+
+const D: usize = 11;
+const N: usize = D * D;
+
+fn main() {
+    // heap owned by the states (board cells, taken cells) is not included
+    let node_size = size_of::<Node<N, Hex<D>>>();
+    let stats_size = size_of::<Stats<N>>();
+    println!(
+        "{:>8} {:>10} {:>10} {:>12} {:>12} {:>12} {:>8}",
+        "sims", "nodes", "est eager", "bytes", "est eager B", "reduction", "ms"
+    );
+    for sim_count in [100, 1_000, 10_000] {
+        let mut tree = Tree::<N, Hex<D>>::with_seed(SearchConfig::new().with_noise(None), 42);
+        let start = Instant::now();
+        tree.simulate(tree.root_ref(), sim_count, &UniformEvaluator);
+        let elapsed = start.elapsed();
+
+        let nodes = tree.nodes().len();
+        let edges = tree.nodes().iter().map(|node| node.action_count()).sum::<usize>();
+        // estimate: eager expansion has a node for every edge, plus the root, the same simulations
+        // visiting the same states
+        let eager_nodes = edges + 1;
+        let bytes = nodes * node_size + edges * stats_size;
+        let eager_bytes = eager_nodes * node_size + edges * stats_size;
+        println!(
+            "{:>8} {:>10} {:>10} {:>12} {:>12} {:>11.1}x {:>8}",
+            sim_count,
+            nodes,
+            eager_nodes,
+            bytes,
+            eager_bytes,
+            eager_bytes as f32 / bytes as f32,
+            elapsed.as_millis()
+        );
+    }
+}
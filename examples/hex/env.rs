@@ -1,12 +1,13 @@
 use std::default::Default;
 use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::iter::once;
 use itertools::Itertools;
 use Player::{Blue, Red};
 use search_rl::env::{Action, State};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Player {
     Red,
     Blue,
@@ -26,6 +27,11 @@ impl Player {
         match self { Red => Blue, Blue => Red }
     }
 
+    /// index of the player in the outcome, Red moving first
+    pub fn index(&self) -> usize {
+        match self { Red => 0, Blue => 1 }
+    }
+
     pub fn occupies<const N: usize>(&self, board: &Board<N>, cell: &Cell) -> bool {
         board
             .at(cell)
@@ -139,7 +145,7 @@ impl Cell {
 }
 
 pub fn cell(i: usize, j_plus_one: usize) -> Cell { Cell::new((i, j_plus_one-1)) }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Hash)]
 pub struct Board<const N: usize>([[Option<Player>; N]; N]);
 
 impl <const N: usize> Display for Board<N> {
@@ -223,26 +229,17 @@ impl<const N: usize> Board<N> {
             .flat_map(|next| next.into_iter())
     }
 
-    pub fn as_array(&self) -> [[i32; N]; N] {
-        self.0
-            .iter()
-            .map(|col| col
-                .iter()
-                .map(|c| match c {
-                    None => 0,
-                    Some(Red) => 1,
-                    Some(Blue) => -1,
-                })
-                .collect_array().unwrap()
-            )
-            .collect_array().unwrap()
+    /// plane with a one at each cell of the player
+    pub fn plane(&self, player: Player) -> [[f32; N]; N] {
+        self.0.map(|col| col.map(|c| if c == Some(player) { 1.0 } else { 0.0 }))
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Hex<const N: usize> {
     board: Board<N>,
-    empty_cells: HashSet<Cell>,
+    /// empty cells in board order, action k takes the k-th one
+    empty_cells: Vec<Cell>,
     taken: Vec<Cell>,
     next: Player,
     winner: Option<Player>,
@@ -252,7 +249,7 @@ impl<const N: usize> Default for Hex<N> {
     fn default() -> Self {
         Self {
             board: Default::default(),
-            empty_cells: (0..N).zip(0..N).map(Cell::new).collect(),
+            empty_cells: (0..N).cartesian_product(0..N).map(Cell::new).collect(),
             taken: Vec::new(),
             next: Red,
             winner: None,
@@ -270,13 +267,21 @@ impl<const N: usize> Hex<N> {
         self.next
     }
 
+    /// empty cells in board order, action k takes the k-th one
     #[allow(dead_code)]
     pub fn empty_cell_iter(&self) -> impl Iterator<Item = Cell> + '_ {
         self.empty_cells.iter().copied()
     }
 
+    /// cell taken by the action
+    pub fn action_cell(&self, action: Action) -> Cell {
+        self.empty_cells[action.index()]
+    }
+
     pub fn next_take_cell(&mut self, cell: Cell) -> bool {
-        _ = self.empty_cells.remove(&cell);
+        if let Ok(index) = self.empty_cells.binary_search(&cell) {
+            self.empty_cells.remove(index);
+        }
         _ = self.board.set(&cell, self.next);
         self.taken.push(cell);
         self.winner = self.next.wins(&self.board).then_some(self.next);
@@ -296,7 +301,9 @@ impl<const N: usize> Hex<N> {
     pub fn undo(&mut self) {
         while let Some(cell) = self.taken.pop() {
             self.board.clear(&cell);
-            _ = self.empty_cells.insert(cell);
+            if let Err(index) = self.empty_cells.binary_search(&cell) {
+                self.empty_cells.insert(index, cell);
+            }
             self.next = self.next.other()
         }
     }
@@ -313,37 +320,71 @@ impl<const N: usize> Display for Hex<N> {
     }
 }
 
-impl<const N: usize> State<N> for Hex<N> {
+impl<const N: usize> State for Hex<N> {
+    const ACTION_SPACE: usize = N * N;
+
+    /// the cells of red, the cells of blue, and the side to move
+    const CHANNELS: usize = 3;
+    const HEIGHT: usize = N;
+    const WIDTH: usize = N;
+
     fn init() -> Self {
         Self::default()
     }
 
     fn action_count(&self) -> usize {
-        self.taken.len()
+        self.empty_cells.len()
+    }
+
+    fn action_name(&self, action: Action) -> String {
+        self.action_cell(action).to_string()
     }
 
     fn take(&self, action: Action) -> Self {
-        let cell = self.taken[action.index()];
+        let cell = self.action_cell(action);
         let mut moved = self.clone();
         moved.next_take_cell(cell);
         moved
     }
 
-    fn reward(&self) -> Option<f32> {
-        if self.is_draw() {
-            return Some(0.1)
-        }
-        if let Some(player) = self.winner() {
-            return Some(match player { Red => 1.0, Blue => -1.0 })
-        }
-        None
+    fn action_id(&self, action: Action) -> usize {
+        let Cell(i, j) = self.action_cell(action);
+        i * N + j
+    }
+
+    fn to_play(&self) -> usize {
+        self.next.index()
     }
 
-    fn value(&self, _taken: Action, value: f32) -> f32 {
-        if value != 0.1 { -value } else { value }
+    fn is_terminal(&self) -> bool {
+        self.winner.is_some() || self.is_draw()
     }
 
-    fn as_array(&self) -> [[i32; N]; N] {
-        self.board.as_array()
+    /// one for the winner and minus one for the loser, zero for both on a draw
+    fn outcome(&self) -> Option<Vec<f32>> {
+        if let Some(player) = self.winner() {
+            let mut outcome = vec![-1.0; 2];
+            outcome[player.index()] = 1.0;
+            return Some(outcome)
+        }
+        self.is_draw().then(|| vec![0.0; 2])
+    }
+
+    fn observation(&self) -> Vec<f32> {
+        let to_play = [[self.to_play() as f32; N]; N];
+        [self.board.plane(Red), self.board.plane(Blue), to_play]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect()
+    }
+
+    /// hash of the board and the player to move, both also in the observation that tells apart
+    /// states whose hashes collide
+    fn transposition_hash(&self) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        self.board.hash(&mut hasher);
+        self.next.hash(&mut hasher);
+        Some(hasher.finish())
     }
 }
\ No newline at end of file
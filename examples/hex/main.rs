@@ -1,4 +1,7 @@
 use crate::env::{cell, Hex, A, B, C, D, E};
+use search_rl::mcts::config::SearchConfig;
+use search_rl::mcts::evaluator::RolloutEvaluator;
+use search_rl::mcts::Tree;
 
 mod env;
 
@@ -25,5 +28,28 @@ fn main() {
         _ = hex.next_take_cell(cell_take);
         println!("{next} {cell_take}\n{}\n", hex);
     }
-    hex.undo()
+    hex.undo();
+
+    play_uct();
+}
+
+/// classic UCT with random rollouts playing both sides
+fn play_uct() {
+    println!("UCT self-play");
+    const N: usize = 5;
+    let config = SearchConfig::new()
+        .with_sim_count(2_000)
+        .with_noise(None)
+        .with_temperature_moves(Some(0));
+    let evaluator = RolloutEvaluator::new(1, 42);
+    let mut tree = Tree::<{ N * N }, Hex<N>>::with_seed(config, 42);
+    while !tree.root().is_terminal() {
+        let root_ref = tree.root_ref();
+        tree.simulate(root_ref, tree.config().sim_count, &evaluator);
+        let next = tree.root().state().next();
+        print!("{}", tree.analysis());
+        let action = tree.sample_action(tree.config().final_temperature);
+        tree.advance(action);
+        println!("{next}\n{}\n", tree.root().state());
+    }
 }
@@ -0,0 +1,169 @@
+use std::fmt::{Display, Formatter};
+use search_rl::env::{Action, State};
+
+/// score to reach to win
+pub const GOAL: u32 = 20;
+
+const ROLL: usize = 0;
+const HOLD: usize = 1;
+
+/// Pig: on their turn a player rolls a die as often as they like, adding each face to the turn
+/// total, until they hold, banking the turn total, or roll a one, losing it. The first player to
+/// bank `GOAL` wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pig {
+    scores: [u32; 2],
+    turn_total: u32,
+    /// index of the player to move, or of the player rolling while the die is in the air
+    next: usize,
+    /// whether the die is in the air, chance moving next with the faces as outcomes
+    rolling: bool,
+}
+
+impl Pig {
+    pub fn winner(&self) -> Option<usize> {
+        self.scores.iter().position(|score| *score >= GOAL)
+    }
+
+    fn pass_turn(&mut self) {
+        self.turn_total = 0;
+        self.next = 1 - self.next;
+    }
+}
+
+impl Display for Pig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let [first, second] = self.scores;
+        write!(f, "{first}-{second}, player {} ", self.next)?;
+        match (self.winner(), self.rolling) {
+            (Some(winner), _) => write!(f, "lost to player {winner}"),
+            (None, true) => write!(f, "rolling for {}", self.turn_total),
+            (None, false) => write!(f, "to move with {}", self.turn_total),
+        }
+    }
+}
+
+impl State for Pig {
+    /// roll, hold and the six faces
+    const ACTION_SPACE: usize = 8;
+    /// a flat feature vector
+    const HEIGHT: usize = 1;
+    const WIDTH: usize = 4;
+
+    fn init() -> Self {
+        Self::default()
+    }
+
+    /// roll or hold, or the six faces of the die in the air
+    fn action_count(&self) -> usize {
+        if self.rolling { 6 } else { 2 }
+    }
+
+    fn action_id(&self, action: Action) -> usize {
+        if self.rolling { 2 + action.index() } else { action.index() }
+    }
+
+    fn chance_outcomes(&self) -> Option<Vec<f32>> {
+        self.rolling.then(|| vec![1.0 / 6.0; 6])
+    }
+
+    fn action_name(&self, action: Action) -> String {
+        match (self.rolling, action.index()) {
+            (true, face) => (face + 1).to_string(),
+            (false, ROLL) => "roll".to_string(),
+            (false, _) => "hold".to_string(),
+        }
+    }
+
+    fn take(&self, action: Action) -> Self {
+        let mut taken = self.clone();
+        match (self.rolling, action.index()) {
+            (true, 0) => {
+                taken.rolling = false;
+                taken.pass_turn();
+            }
+            (true, face) => {
+                taken.rolling = false;
+                taken.turn_total += face as u32 + 1;
+            }
+            (false, ROLL) => taken.rolling = true,
+            (false, HOLD) => {
+                taken.scores[self.next] += self.turn_total;
+                taken.pass_turn();
+            }
+            (false, _) => panic!("action should be roll or hold"),
+        }
+        taken
+    }
+
+    /// the player rolling while the die is in the air, the turn passing on holding or rolling
+    /// a one
+    fn to_play(&self) -> usize {
+        self.next
+    }
+
+    fn outcome(&self) -> Option<Vec<f32>> {
+        self.winner().map(|winner| {
+            let mut outcome = vec![-1.0; 2];
+            outcome[winner] = 1.0;
+            outcome
+        })
+    }
+
+    /// the scores, and the turn total with the player to move, as fractions of the goal
+    fn observation(&self) -> Vec<f32> {
+        let [first, second] = self.scores.map(|score| score as f32 / GOAL as f32);
+        let turn_total = self.turn_total as f32 / GOAL as f32;
+        vec![first, second, turn_total, self.next as f32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(state: &Pig, index: usize) -> Action {
+        state.action_iter().nth(index).expect("action should exist")
+    }
+
+    #[test]
+    fn rolling_is_a_chance_move() {
+        let pig = Pig::init();
+        assert_eq!(pig.chance_outcomes(), None);
+        let rolling = pig.take(action(&pig, ROLL));
+        let probabilities = rolling.chance_outcomes().expect("die should be in the air");
+        assert_eq!(probabilities.len(), 6);
+        assert!((probabilities.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rolling_a_one_passes_the_turn() {
+        let pig = Pig { turn_total: 5, ..Pig::init() };
+        let rolling = pig.take(action(&pig, ROLL));
+        let rolled = rolling.take(action(&rolling, 0));
+        assert_eq!((rolled.to_play(), rolled.turn_total), (1, 0));
+        let rolled = rolling.take(action(&rolling, 3));
+        assert_eq!((rolled.to_play(), rolled.turn_total), (0, 9));
+    }
+
+    #[test]
+    fn faces_have_their_own_action_ids() {
+        let pig = Pig::init();
+        let rolling = pig.take(action(&pig, ROLL));
+        assert_eq!(pig.legal_actions(), [true, true, false, false, false, false, false, false]);
+        assert_eq!(rolling.legal_actions(), [false, false, true, true, true, true, true, true]);
+        assert_eq!(rolling.action_of_id(4), Some(action(&rolling, 2)));
+        assert_eq!(rolling.action_of_id(HOLD), None);
+    }
+
+    #[test]
+    fn holding_to_the_goal_wins() {
+        let pig = Pig { scores: [GOAL - 4, 0], turn_total: 4, ..Pig::init() };
+        let held = pig.take(action(&pig, HOLD));
+        assert_eq!(held.winner(), Some(0));
+        assert!(held.is_terminal());
+        // the winner held last, so the player to move has lost
+        assert_eq!(held.reward(), Some(-1.0));
+        assert_eq!(held.outcome(), Some(vec![1.0, -1.0]));
+    }
+}
@@ -0,0 +1,32 @@
+use crate::env::Pig;
+use search_rl::env::State;
+use search_rl::mcts::config::SearchConfig;
+use search_rl::mcts::evaluator::RolloutEvaluator;
+use search_rl::mcts::Tree;
+
+mod env;
+
+/// UCT with random rollouts playing both sides of Pig, the die rolls being chance nodes
+fn main() {
+    println!("Hello, pig!");
+    let config = SearchConfig::new()
+        .with_sim_count(2_000)
+        .with_noise(None)
+        .with_temperature_moves(Some(0))
+        .with_seed(Some(42));
+    let evaluator = RolloutEvaluator::new(1, 42);
+    let mut tree = Tree::<8, Pig>::new(config);
+    while !tree.root().is_terminal() {
+        let state = tree.root().state().clone();
+        let action = if tree.root().is_chance() {
+            tree.sample_outcome()
+        } else {
+            let root_ref = tree.root_ref();
+            tree.simulate(root_ref, tree.config().sim_count, &evaluator);
+            tree.sample_action(tree.config().final_temperature)
+        };
+        println!("{state}: {}", state.action_name(action));
+        tree.advance(action);
+    }
+    println!("{}", tree.root().state());
+}
@@ -8,6 +8,7 @@ fn main() {
     let model = ModelConfig::new(
         11, 11,
         3,
+        3,
         121,
     0.5).init::<MyBackend>(&device);
 
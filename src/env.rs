@@ -1,4 +1,3 @@
-use smallvec::{Array, SmallVec};
 use std::ops::{Index, IndexMut};
 
 /// Action that can be taken from a state
@@ -11,8 +10,23 @@ impl Action {
     }
 }
 
-/// D is the dimension of the state square D x D
-pub trait State<const D: usize> /*: Clone + Copy*/ {
+/// state of a game, observed as `CHANNELS` planes of `HEIGHT` x `WIDTH` features
+pub trait State /*: Clone + Copy*/ {
+
+    /// size of the fixed action space every state maps its actions into, e.g. the cells of the
+    /// board, the size of the policy head of a model
+    const ACTION_SPACE: usize;
+
+    /// number of feature planes of the observation of a state
+    const CHANNELS: usize = 1;
+
+    /// height of the observation planes, e.g. the rows of the board, one for a flat feature
+    /// vector
+    const HEIGHT: usize;
+
+    /// width of the observation planes, e.g. the columns of the board, or the length of a flat
+    /// feature vector
+    const WIDTH: usize;
 
     /// initial state
     fn init() -> Self;
@@ -22,31 +36,96 @@ pub trait State<const D: usize> /*: Clone + Copy*/ {
     
     /// iterate over the actions
     fn action_iter(&self) -> impl Iterator<Item = Action> + '_ {
-        (0..self.action_count()).map(|index| Action(index))
+        (0..self.action_count()).map(Action)
+    }
+
+    /// id of the action in the action space, below `ACTION_SPACE` and the same in every state
+    /// making that move, e.g. the cell played
+    fn action_id(&self, action: Action) -> usize {
+        action.index()
+    }
+
+    /// action of the state with the given id in the action space, none if not legal here
+    fn action_of_id(&self, id: usize) -> Option<Action> {
+        self.action_iter().find(|action| self.action_id(*action) == id)
+    }
+
+    /// legal-action mask over the action space, true at the id of each action of the state
+    fn legal_actions(&self) -> Vec<bool> {
+        let mut legal = vec![false; Self::ACTION_SPACE];
+        self.action_iter().for_each(|action| legal[self.action_id(action)] = true);
+        legal
+    }
+
+    /// the move taken by the action in the notation of the game, for printing moves
+    fn action_name(&self, action: Action) -> String {
+        action.index().to_string()
+    }
+
+    /// probability of each chance outcome when chance moves next, e.g. the faces of a die
+    /// rolled, the outcomes being the actions of the state, none when a player moves next
+    fn chance_outcomes(&self) -> Option<Vec<f32>> {
+        None
     }
 
     /// state resulting from taking given action
     fn take(&self, action: Action) -> Self;
 
-    /// reward for terminal state, none for non-terminal state
-    fn reward(&self) -> Option<f32>;
+    /// number of players, values being vectors indexed by player
+    fn player_count(&self) -> usize {
+        2
+    }
+
+    /// index of the player to move, whose component of the values the search maximizes, or for
+    /// a chance state, of the player who moved into it
+    fn to_play(&self) -> usize;
+
+    /// whether the game is over, by default whether there is an outcome
+    fn is_terminal(&self) -> bool {
+        self.outcome().is_some()
+    }
+
+    /// reward of terminal state for every player, indexed as `to_play`, none for non-terminal
+    /// state, in [-1, 1] unless the search normalizes Q values
+    fn outcome(&self) -> Option<Vec<f32>>;
+
+    /// reward for terminal state from the perspective of the player to move, none for
+    /// non-terminal state
+    fn reward(&self) -> Option<f32> {
+        self.outcome().map(|outcome| outcome[self.to_play()])
+    }
+
+    /// value of this state for every player given its value for the player to move, e.g. from
+    /// the scalar value head of a model, by default zero-sum with the other players sharing the
+    /// loss equally; override for cooperative or general-sum games
+    fn values(&self, value: f32) -> Vec<f32> {
+        let player_count = self.player_count();
+        let others = -value / player_count.saturating_sub(1).max(1) as f32;
+        (0..player_count)
+            .map(|player| if player == self.to_play() { value } else { others })
+            .collect()
+    }
 
-    /// value for this state given value for state resulting from taking given action
-    fn value(&self, taken: Action, value: f32) -> f32;
+    /// observation of the state for a model, `CHANNELS` planes of `HEIGHT` x `WIDTH` features
+    /// flattened plane by plane and row by row, e.g. the stones of each player and the side to
+    /// move
+    fn observation(&self) -> Vec<f32>;
 
-    /// float array representation for the state
-    fn as_array(&self) -> [[i32; D]; D];
+    /// hash identifying equal states reached by different move orders, none if the state should
+    /// never share a node with another; states only share a node when their observations are
+    /// equal too, so a collision of the hashes of different states does not merge them
+    fn transposition_hash(&self) -> Option<u64> {
+        None
+    }
 }
 
-pub struct ActionMap<T: Array + Sized>(SmallVec<T>);
+pub struct ActionMap<T>(Vec<T>);
 
 
 
-impl<T: Array<Item = T> + Sized> ActionMap<T> {
+impl<T> ActionMap<T> {
     pub fn new(items: impl IntoIterator<Item = T>) -> Self {
-        let mut v = SmallVec::<T>::new();
-        v.extend(items);
-        Self(v)
+        Self(items.into_iter().collect())
     }
     
     pub fn as_slice(&self) -> &[T] {
@@ -82,7 +161,7 @@ impl<T: Array<Item = T> + Sized> ActionMap<T> {
     }
 }
 
-impl<T: Array<Item = T> + Sized> Index<Action> for ActionMap<T> {
+impl<T> Index<Action> for ActionMap<T> {
     type Output = T;
 
     fn index(&self, action: Action) -> &Self::Output {
@@ -90,7 +169,7 @@ impl<T: Array<Item = T> + Sized> Index<Action> for ActionMap<T> {
     }
 }
 
-impl<T: Array<Item = T> + Sized> IndexMut<Action> for ActionMap<T> {
+impl<T> IndexMut<Action> for ActionMap<T> {
     fn index_mut(&mut self, action: Action) -> &mut Self::Output {
         &mut self.0[action.index()]
     }
@@ -1,13 +1,27 @@
+pub mod analysis;
+pub mod config;
+pub mod evaluator;
+pub mod export;
+mod gumbel;
+
 use crate::env::{Action, ActionMap, State};
-use itertools::Itertools;
+use config::{NodeBudgetConfig, NoiseConfig, SearchConfig};
+use evaluator::{Evaluation, Evaluator};
+use itertools::{Either, Itertools};
 use ordered_float::OrderedFloat;
-use rand::random_range;
-use smallvec::Array;
-use std::cell::OnceCell;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Gamma};
+use std::collections::{HashMap, HashSet};
 use std::default::Default;
 use std::ops::{Index, IndexMut};
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use std::sync::atomic::{AtomicU32, AtomicUsize};
+use std::sync::{OnceLock, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct NodeRef<const N: usize>(usize);
 
 impl<const N: usize> NodeRef<N> {
@@ -16,237 +30,845 @@ impl<const N: usize> NodeRef<N> {
     }
 }
 
-unsafe impl<const N: usize> Array for NodeRef<N> {
-    type Item = NodeRef<N>;
+pub struct F32<const A: usize>(pub f32);
+
+/// best reward of a player under the `State::outcome` contract, no other action doing better than
+/// one proven to reach it
+const BEST_REWARD: f32 = 1.0;
+
+/// game theoretic value proven by the search, for the player to move at a node or the player
+/// taking an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Proven {
+    Win,
+    Loss,
+    Draw,
+}
 
-    fn size() -> usize {
-        N
+impl Proven {
+    /// classify a proven value for reporting, positive wins, negative loses and zero draws, the
+    /// search itself comparing proven values exactly
+    pub fn of(value: f32) -> Self {
+        if value > 0.0 {
+            Proven::Win
+        } else if value < 0.0 {
+            Proven::Loss
+        } else {
+            Proven::Draw
+        }
     }
 }
 
-pub struct F32<const A: usize>(pub f32);
+/// f32 shared between threads, updated atomically through its bits
+#[derive(Default)]
+struct AtomicF32(AtomicU32);
 
-unsafe impl<const N: usize> Array for F32<N> {
-    type Item = F32<N>;
+impl AtomicF32 {
+    fn load(&self) -> f32 {
+        f32::from_bits(self.0.load(Relaxed))
+    }
+
+    fn add(&self, value: f32) {
+        self.update(|current| current + value)
+    }
 
-    fn size() -> usize {
-        N
+    fn update(&self, f: impl Fn(f32) -> f32) {
+        _ = self.0.fetch_update(Relaxed, Relaxed, |bits| Some(f(f32::from_bits(bits)).to_bits()))
     }
 }
 
-#[derive(Default)]
+/// MuZero running minimum and maximum of the Q values in the tree, normalizing them to [0, 1]
+/// so the exploration term stays in scale whatever the range of the rewards
+pub struct MinMaxStats {
+    min: AtomicF32,
+    max: AtomicF32,
+}
+
+impl Default for MinMaxStats {
+    fn default() -> Self {
+        Self {
+            min: AtomicF32(AtomicU32::new(f32::INFINITY.to_bits())),
+            max: AtomicF32(AtomicU32::new(f32::NEG_INFINITY.to_bits())),
+        }
+    }
+}
+
+impl MinMaxStats {
+    fn update(&self, value: f32) {
+        self.min.update(|min| min.min(value));
+        self.max.update(|max| max.max(value));
+    }
+
+    /// minimum and maximum seen so far, none before any value
+    pub fn bounds(&self) -> Option<(f32, f32)> {
+        let (min, max) = (self.min.load(), self.max.load());
+        (min <= max).then_some((min, max))
+    }
+
+    /// value rescaled by the bounds seen so far, unchanged until they differ
+    pub fn normalize(&self, value: f32) -> f32 {
+        let (min, max) = (self.min.load(), self.max.load());
+        if max > min {
+            (value - min) / (max - min)
+        } else {
+            value
+        }
+    }
+}
+
+/// size and reuse of the slots of the arena
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ArenaStats {
+    /// nodes in the arena
+    pub live: usize,
+    /// slots allocated, holding a node or free
+    pub slots: usize,
+    /// free slots awaiting reuse
+    pub free: usize,
+    /// nodes added into reused slots so far
+    pub reused: usize,
+    /// nodes freed by garbage collection so far
+    pub collected: usize,
+}
+
+/// arena of the nodes of the search tree, or of the search DAG when equal states share one node
+/// through the transposition table, reusing the slots of collected nodes
 pub struct Nodes<
     const N: usize,
-    const D: usize,
-    S: State<D>,
->(Vec<Node<N, D, S>>);
+    S: State,
+> {
+    nodes: Vec<Option<Node<N, S>>>,
+    /// indices of the free slots
+    free: Vec<usize>,
+    /// most nodes in the arena, none for unbounded
+    max_nodes: Option<usize>,
+    reused: usize,
+    collected: usize,
+    /// node of each state hash, none when transpositions are off
+    transpositions: Option<HashMap<u64, NodeRef<N>>>,
+    /// whether actions keep all-moves-as-first statistics for RAVE
+    amaf: bool,
+    /// bounds of the Q values when normalizing them
+    min_max: Option<MinMaxStats>,
+}
+
+impl<
+    const N: usize,
+    S: State,
+> Default for Nodes<N, S> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            max_nodes: None,
+            reused: 0,
+            collected: 0,
+            transpositions: None,
+            amaf: false,
+            min_max: None,
+        }
+    }
+}
 
 impl<
     const N: usize,
-    const D: usize,
-    S: State<D>,
-> Nodes<N, D, S> {
-    pub fn new() -> (NodeRef<N>, Self) {
-        let mut nodes = Self(Vec::new());
-        let root_ref = nodes.add_node(S::init());
+    S: State,
+> Nodes<N, S> {
+    pub fn new(config: &SearchConfig) -> (NodeRef<N>, Self) {
+        Self::with_root(S::init(), config)
+    }
+
+    fn with_root(state: S, config: &SearchConfig) -> (NodeRef<N>, Self) {
+        let mut nodes = Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            max_nodes: config.node_budget.as_ref().map(|budget| budget.max_nodes),
+            reused: 0,
+            collected: 0,
+            transpositions: config.transpositions.then(HashMap::new),
+            amaf: config.rave.is_some(),
+            min_max: config.normalize_q.then(MinMaxStats::default),
+        };
+        let root_ref = nodes.add_node(state);
         (root_ref, nodes)
     }
 
-    /// select an action taking to child node/state
-    pub fn select_action(&mut self, node_ref: NodeRef<N>) -> (Action, NodeRef<N>) {
-        self.open_if_not(node_ref);
-        self[node_ref].select_action(3.0)
+    /// select an action taking to child node/state, counting a virtual loss for the action
+    /// until a value is backed up through it
+    pub fn select_action(
+        &mut self,
+        node_ref: NodeRef<N>,
+        config: &SearchConfig,
+    ) -> (Action, NodeRef<N>) {
+        let action = self[node_ref].select_action(config, self.min_max.as_ref());
+        (action, self.take_action(node_ref, action))
+    }
+
+    /// child node reached by taking the given action, counting a virtual loss for the action
+    /// until a value is backed up through it
+    pub fn take_action(&mut self, node_ref: NodeRef<N>, action: Action) -> NodeRef<N> {
+        self[node_ref].action_stats()[action].add_virtual_loss();
+        self.child(node_ref, action)
     }
 
     pub fn sample_action(
-        &mut self,
+        &self,
         node_ref: NodeRef<N>,
         temperature: f32,
-    ) -> (Action, NodeRef<N>) {
-        self.open_if_not(node_ref);
-        self[node_ref].sample_action(temperature)
+        rng: &mut impl Rng,
+    ) -> Action {
+        self[node_ref].sample_action(temperature, rng)
+    }
+
+    /// child node reached by taking the action, created the first time it is needed
+    pub fn child(&mut self, node_ref: NodeRef<N>, action: Action) -> NodeRef<N> {
+        if let Some(child_ref) = self[node_ref].action_stats()[action].child() {
+            return child_ref;
+        }
+        let child_state = self[node_ref].state.take(action);
+        let child_ref = self.add_node(child_state);
+        _ = self[node_ref].action_stats()[action].child.set(child_ref);
+        child_ref
+    }
+
+    /// whether the child reached by taking the action exists or fits in the node budget
+    pub fn has_room(&self, node_ref: NodeRef<N>, action: Action) -> bool {
+        self[node_ref].action_stats()[action].child().is_some()
+            || self.max_nodes.is_none_or(|max_nodes| self.len() < max_nodes)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Node<N, S>> {
+        self.nodes.iter().flatten()
+    }
+
+    /// number of nodes in the arena
+    pub fn len(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn arena_stats(&self) -> ArenaStats {
+        ArenaStats {
+            live: self.len(),
+            slots: self.nodes.len(),
+            free: self.free.len(),
+            reused: self.reused,
+            collected: self.collected,
+        }
     }
 
+    /// add a node for the state, or with transpositions on, find the node already added for an
+    /// equal state, one with the same hash and observation
     fn add_node(&mut self, state: S) -> NodeRef<N> {
-        let index = self.0.len();
-        self.0.push(Node::new(state));
-        NodeRef(index)
+        let node_ref = NodeRef(self.free.last().copied().unwrap_or(self.nodes.len()));
+        let hash = self.transpositions.is_some().then(|| state.transposition_hash()).flatten();
+        if let Some(hash) = hash {
+            let transpositions = self.transpositions.as_ref().expect("transpositions are on");
+            match transpositions.get(&hash).copied() {
+                Some(transposition_ref)
+                    if self[transposition_ref].state.observation() == state.observation() =>
+                {
+                    return transposition_ref;
+                }
+                // a hash collision between different states, the new one gets a node of its own
+                Some(_) => {}
+                None => {
+                    self.transpositions
+                        .as_mut()
+                        .expect("transpositions are on")
+                        .insert(hash, node_ref);
+                }
+            }
+        }
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(Node::new(state));
+                self.reused += 1;
+            }
+            None => self.nodes.push(Some(Node::new(state))),
+        }
+        node_ref
     }
 
     fn is_open(&self, node_ref: NodeRef<N>) -> bool {
         self[node_ref].has_actions()
     }
 
-    fn open_if_not(&mut self, node_ref: NodeRef<N>) {
-        if self.is_open(node_ref) {
-            return;
+    /// expand a leaf with its evaluation, leaving the children to be created when first selected
+    fn expand(&self, node_ref: NodeRef<N>, evaluation: Evaluation<N>) {
+        let state = &self[node_ref].state;
+        _ = self[node_ref].value.set(evaluation.value[state.to_play()]);
+        let amaf_keys = self.amaf.then(|| {
+            state
+                .action_iter()
+                .map(|action| state.action_id(action))
+                .collect_vec()
+        });
+        self[node_ref].init_actions(evaluation.priors, amaf_keys)
+    }
+
+    /// Back-propagate the values V(s) of every player for the leaf or terminal state to the
+    /// state that led to it, each action recording the value of the player taking it;
+    /// increment the number of times each state-action pair N(s, a) was visited along the path
+    /// from the root node to the leaf or terminal node; adjust N(s), W(s, a), and Q(s, a) for
+    /// all the states and actions along this path. With transpositions the statistics stay per
+    /// edge, so a node shared by several parents only counts toward the edges actually taken to
+    /// reach it, while its own N(s) counts the visits through any of them
+    fn backup(
+        &self,
+        leaf_ref: NodeRef<N>,
+        back: &mut Vec<(NodeRef<N>, Action)>,
+        mut values: Vec<f32>,
+        action_ids: &[HashSet<usize>],
+    ) {
+        self[leaf_ref].visit_count.fetch_add(1, Release);
+        let mut proven = self[leaf_ref].proven_values().map(<[f32]>::to_vec);
+        let amaf_keys = self.amaf.then(|| {
+            back.iter()
+                .map(|(prev_ref, action)| self[*prev_ref].action_stats()[*action].amaf_key())
+                .collect_vec()
+        });
+        // moves made by each player from a depth on, starting with those of the rollouts
+        let mut later_keys = match self.amaf {
+            true => action_ids.iter().cloned().enumerate().collect(),
+            false => HashMap::<usize, HashSet<usize>>::new(),
+        };
+        while let Some((prev_ref, action)) = back.pop() {
+            let prev = &self[prev_ref];
+            prev.visit_count.fetch_add(1, Release);
+            let player = prev.state.to_play();
+            let value = values[player];
+            let stats = &prev.action_stats()[action];
+            stats.record(value);
+            prev.record_outcome(action, &values);
+            if let Some((min_max, quality)) = self.min_max.as_ref().zip(stats.quality()) {
+                min_max.update(quality);
+            }
+            if let Some(amaf_keys) = &amaf_keys {
+                // RAVE: the value counts for every move the player made from here on, as if
+                // made first
+                let later_keys = later_keys.entry(player).or_default();
+                later_keys.extend(amaf_keys[back.len()]);
+                prev.action_stats()
+                    .iter()
+                    .filter_map(|stats| stats.amaf.as_ref())
+                    .filter(|amaf| later_keys.contains(&amaf.key))
+                    .for_each(|amaf| amaf.record(value));
+            }
+            // MCTS-solver: a proven child proves the action, which may prove the state in turn
+            proven = proven.and_then(|child_values| {
+                stats.prove(child_values[player]);
+                self.prove(prev_ref)
+            });
+            if let Some(expected_values) = prev.expected_values() {
+                // expectation backup, the values of a chance node being the mean of the values of
+                // its outcomes weighted by their probabilities
+                values = expected_values;
+            }
         }
-        self.init_actions(node_ref);
-        self.add_children(node_ref)
     }
 
-    fn init_actions(&self, node_ref: NodeRef<N>) {
-        let mut last_node_ref = self.0.len() - 1;
-        self[node_ref].init_actions(|| {
-            last_node_ref += 1;
-            NodeRef(last_node_ref)
-        })
+    /// prove the state from its actions, by any action proven to reach the best reward unless
+    /// normalizing Q, or otherwise by the best action once all of them are proven, returning its
+    /// values for every player
+    fn prove(&self, node_ref: NodeRef<N>) -> Option<Vec<f32>> {
+        let node = &self[node_ref];
+        if let Some(values) = node.proven_values() {
+            return Some(values.to_vec());
+        }
+        let action_stats = node.action_stats.get()?;
+        let child_values = |stats: &Stats<N>| {
+            stats.proven_value()?;
+            Some(self[stats.child()?].proven_values()?.to_vec())
+        };
+        let values = if node.is_chance() {
+            // a chance node is proven by the expectation once all of its outcomes are proven
+            let mut values = vec![0.0; node.state.player_count()];
+            for stats in action_stats.iter() {
+                for (value, child_value) in values.iter_mut().zip(child_values(stats)?) {
+                    *value += stats.prior() * child_value;
+                }
+            }
+            values
+        } else {
+            // nothing beats the best reward of the outcome contract, but any lesser proven value
+            // may, and rewards are unbounded with normalized Q, so every action has to be proven
+            // to know the best
+            let best_reward = action_stats.iter().find(|stats| {
+                self.min_max.is_none()
+                    && stats.proven_value().is_some_and(|value| value >= BEST_REWARD)
+            });
+            let best = match best_reward {
+                Some(stats) => stats,
+                None => action_stats
+                    .iter()
+                    .map(|stats| Some((stats, stats.proven_value()?)))
+                    .collect::<Option<Vec<_>>>()?
+                    .into_iter()
+                    .max_by_key(|(_, value)| OrderedFloat(*value))?
+                    .0,
+            };
+            child_values(best)?
+        };
+        Some(node.proven.get_or_init(|| values).clone())
     }
 
-    fn add_children(&mut self, node_ref: NodeRef<N>) {
-        let state = &self[node_ref].state;
-        let child_states = state
-            .action_iter()
-            .map(|action| state.take(action))
+    /// free the nodes not reachable from the given ones, returning them with their refs
+    fn sweep(&mut self, root_refs: &[NodeRef<N>]) -> Vec<(NodeRef<N>, Node<N, S>)> {
+        let mut reachable = vec![false; self.nodes.len()];
+        let mut stack = root_refs.to_vec();
+        while let Some(curr_ref) = stack.pop() {
+            if std::mem::replace(&mut reachable[curr_ref.index()], true) {
+                continue;
+            }
+            if let Some(action_stats) = self[curr_ref].action_stats.get() {
+                stack.extend(action_stats.iter().filter_map(|stats| stats.child()));
+            }
+        }
+        let swept = self
+            .nodes
+            .iter_mut()
+            .enumerate()
+            .filter(|(index, _)| !reachable[*index])
+            .filter_map(|(index, node)| Some((NodeRef(index), node.take()?)))
             .collect_vec();
-        child_states.into_iter().for_each(|child_state| {
-            _ = self.add_node(child_state);
-        })
+        self.free.extend(swept.iter().map(|(node_ref, _)| node_ref.index()));
+        self.collected += swept.len();
+        if let Some(transpositions) = self.transpositions.as_mut() {
+            transpositions.retain(|_, node_ref| reachable[node_ref.index()]);
+        }
+        swept
+    }
+
+    /// move the nodes into the first slots, keeping their order, and drop the free slots once
+    /// there are at least as many of them as nodes, returning the given refs remapped
+    fn compact(&mut self, node_refs: &[NodeRef<N>]) -> Vec<NodeRef<N>> {
+        if self.free.len() < self.len() {
+            return node_refs.to_vec();
+        }
+        let mut remap = vec![None; self.nodes.len()];
+        let mut next = 0;
+        for (index, node) in self.nodes.iter().enumerate() {
+            if node.is_some() {
+                remap[index] = Some(NodeRef(next));
+                next += 1;
+            }
+        }
+        let remapped = |node_ref: &NodeRef<N>| remap[node_ref.index()].expect("node should be live");
+        self.nodes.retain(Option::is_some);
+        self.free.clear();
+        for node in self.nodes.iter_mut().flatten() {
+            if let Some(action_stats) = node.action_stats.get_mut() {
+                for stats in action_stats.iter_mut() {
+                    if let Some(child_ref) = stats.child.get_mut() {
+                        *child_ref = remapped(child_ref);
+                    }
+                }
+            }
+        }
+        if let Some(transpositions) = self.transpositions.as_mut() {
+            transpositions.values_mut().for_each(|node_ref| *node_ref = remapped(node_ref));
+        }
+        node_refs.iter().map(remapped).collect()
+    }
+
+    /// detach the subtrees of the least visited nodes below the root, keeping the statistics of
+    /// the actions leading to them, and free their nodes until at most keep nodes are left, never
+    /// freeing the root nor the other given nodes
+    fn prune(&mut self, root_ref: NodeRef<N>, keep_refs: &[NodeRef<N>], keep: usize) {
+        if self.len() <= keep {
+            return;
+        }
+        // visits only decrease going down the tree, so the nodes visited more often than the
+        // threshold are the ones nearest the root
+        let threshold = self
+            .iter()
+            .map(|node| node.visit_count())
+            .sorted_unstable_by(|a, b| b.cmp(a))
+            .nth(keep)
+            .unwrap_or(0);
+        let mut detached = Vec::new();
+        let mut stack = vec![root_ref];
+        let mut seen = HashSet::new();
+        while let Some(curr_ref) = stack.pop() {
+            if !seen.insert(curr_ref) {
+                continue;
+            }
+            let Some(action_stats) = self[curr_ref].action_stats.get() else {
+                continue;
+            };
+            for (action, stats) in action_stats.action_value_iter() {
+                match stats.child() {
+                    Some(child_ref) if self[child_ref].visit_count() <= threshold => {
+                        detached.push((curr_ref, action))
+                    }
+                    Some(child_ref) => stack.push(child_ref),
+                    None => {}
+                }
+            }
+        }
+        for (node_ref, action) in detached {
+            if let Some(action_stats) = self[node_ref].action_stats.get_mut() {
+                action_stats[action].child.take();
+            }
+        }
+        let root_refs = [root_ref].into_iter().chain(keep_refs.iter().copied()).collect_vec();
+        self.sweep(&root_refs);
     }
 }
 
 impl<
     const N: usize,
-    const D: usize,
-    S: State<D>,
-> Index<NodeRef<N>> for Nodes<N, D, S> {
-    type Output = Node<N, D, S>;
+    S: State,
+> Index<NodeRef<N>> for Nodes<N, S> {
+    type Output = Node<N, S>;
 
     fn index(&self, index: NodeRef<N>) -> &Self::Output {
-        &self.0[index.index()]
+        self.nodes[index.index()]
+            .as_ref()
+            .expect("node should not be freed")
     }
 }
 
 impl<
     const N: usize,
-    const D: usize,
-    S: State<D>,
-> IndexMut<NodeRef<N>> for Nodes<N, D, S> {
+    S: State,
+> IndexMut<NodeRef<N>> for Nodes<N, S> {
     fn index_mut(&mut self, index: NodeRef<N>) -> &mut Self::Output {
-        &mut self.0[index.index()]
+        self.nodes[index.index()]
+            .as_mut()
+            .expect("node should not be freed")
     }
 }
 
 /// result of taking an action
 pub struct Stats<const A: usize> {
     /// N(s, a): The number of times action a has been taken from state s
-    count: usize,
+    count: AtomicUsize,
     /// W(s, a): The total summed value V obtained from every time we’ve taken action a from
     /// state s in the current search tree
-    total_value: f32,
+    total_value: AtomicF32,
     /// P(s, a): The prior probability of selecting action a from state s; this is the output of
     /// the neural net on state s, except for when s is a root node, where we add Dirichlet
     /// noise (I’ll explain this later) to the neural net output to encourage exploration from the
     /// root node
     prior: f32,
+    /// simulations descending through action a whose value is not backed up yet, counted as
+    /// losses so concurrent simulations spread over different paths
+    virtual_count: AtomicUsize,
+    /// child node reached by taking action a, none until a is first selected
+    child: OnceLock<NodeRef<A>>,
+    /// value of taking action a once proven by the search
+    proven: OnceLock<f32>,
+    /// all-moves-as-first statistics when RAVE is on
+    amaf: Option<Amaf>,
+}
+
+/// all-moves-as-first statistics of an action, from every simulation through the state in which
+/// the player made the same move at any later point
+struct Amaf {
+    /// `State::action_id` of the action
+    key: usize,
+    count: AtomicUsize,
+    total_value: AtomicF32,
+}
+
+impl Amaf {
+    fn new(key: usize) -> Self {
+        Self {
+            key,
+            count: AtomicUsize::new(0),
+            total_value: AtomicF32::default(),
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.count.load(Relaxed)
+    }
+
+    fn quality(&self) -> f32 {
+        self.total_value.load() / self.count() as f32
+    }
+
+    fn record(&self, value: f32) {
+        self.count.fetch_add(1, Relaxed);
+        self.total_value.add(value);
+    }
 }
 
 impl<const A: usize> ActionMap<Stats<A>> {
     pub fn probability_iter(
         &self,
-        visit_count: usize,
         temperature: f32,
     ) -> impl Iterator<Item = F32<A>> + '_ {
-        let denom = (visit_count as f32).powf(1.0 / temperature);
-        self.iter()
-            .map(move |stats| F32((stats.count as f32).powf(1.0 / temperature) / denom))
+        // zero temperature is the limit where the most visited action has all the probability
+        let most_visited = self
+            .action_value_iter()
+            .max_by_key(|(_, stats)| stats.count())
+            .map(|(action, _)| action);
+        let denom = self
+            .iter()
+            .map(|stats| (stats.count() as f32).powf(1.0 / temperature))
+            .sum::<f32>();
+        self.action_value_iter().map(move |(action, stats)| {
+            F32(if temperature > 0.0 {
+                (stats.count() as f32).powf(1.0 / temperature) / denom
+            } else if Some(action) == most_visited {
+                1.0
+            } else {
+                0.0
+            })
+        })
     }
 
-    pub fn quality(&self) -> f32 {
-        self.iter().map(|stats| stats.quality()).sum::<f32>() / self.len() as f32
+    /// mean value over the visits of all actions, none before any visit
+    pub fn quality(&self) -> Option<f32> {
+        let count = self.iter().map(|stats| stats.count()).sum::<usize>();
+        let total_value = self.iter().map(|stats| stats.total_value()).sum::<f32>();
+        (count > 0).then(|| total_value / count as f32)
     }
 
 }
 
 impl<const A: usize> Stats<A> {
-    /// Q(s, a): The mean value V obtained from taking action a from state s, equal to W(s, a) / N(s, a)
-    pub fn quality(&self) -> f32 {
-        self.total_value / self.count as f32
+    fn new(prior: f32, amaf_key: Option<usize>) -> Self {
+        Self {
+            count: AtomicUsize::new(0),
+            total_value: AtomicF32::default(),
+            prior,
+            virtual_count: AtomicUsize::new(0),
+            child: OnceLock::new(),
+            proven: OnceLock::new(),
+            amaf: amaf_key.map(Amaf::new),
+        }
     }
 
-    /// PUCT(s, a) = Q(s, a) + c * P(s, a) * sqrt(N(s))/(1 + N(s, a))
-    pub fn puct(&self, visit_count: usize, explore_factor: f32) -> f32 {
-        self.quality()
-            + explore_factor * self.prior * (visit_count as f32).sqrt() / (1.0 + self.count as f32)
+    /// N(s, a)
+    pub fn count(&self) -> usize {
+        self.count.load(Relaxed)
+    }
+
+    /// W(s, a)
+    pub fn total_value(&self) -> f32 {
+        self.total_value.load()
+    }
+
+    /// P(s, a)
+    pub fn prior(&self) -> f32 {
+        self.prior
+    }
+
+    pub fn child(&self) -> Option<NodeRef<A>> {
+        self.child.get().copied()
+    }
+
+    /// value of taking the action, once proven
+    pub fn proven_value(&self) -> Option<f32> {
+        self.proven.get().copied()
+    }
+
+    /// whether taking the action is a proven win, loss or draw
+    pub fn proven(&self) -> Option<Proven> {
+        self.proven_value().map(Proven::of)
+    }
+
+    fn prove(&self, value: f32) {
+        _ = self.proven.set(value);
+    }
+
+    /// number of simulations counting toward the all-moves-as-first value
+    pub fn amaf_count(&self) -> usize {
+        self.amaf.as_ref().map_or(0, |amaf| amaf.count())
+    }
+
+    /// `State::action_id` of the action, none for chance outcomes, which keep no
+    /// all-moves-as-first statistics
+    fn amaf_key(&self) -> Option<usize> {
+        self.amaf.as_ref().map(|amaf| amaf.key)
+    }
+
+    /// Q(s, a): The mean value V obtained from taking action a from state s, equal to W(s, a) / N(s, a),
+    /// none until the action is taken
+    pub fn quality(&self) -> Option<f32> {
+        let count = self.count();
+        (count > 0).then(|| self.total_value() / count as f32)
+    }
+
+    /// PUCT(s, a) = Q(s, a) + c * P(s, a) * sqrt(N(s))/(1 + N(s, a)), with each pending
+    /// simulation through the action counted as a visit losing the virtual loss, and with RAVE
+    /// on, Q(s, a) blended with the all-moves-as-first value. Q(s, a) of an action not yet taken
+    /// is its all-moves-as-first value, else the first play urgency, else infinite. Q(s, a) is
+    /// normalized by the bounds when given
+    pub fn puct(
+        &self,
+        visit_count: usize,
+        config: &SearchConfig,
+        first_play_urgency: Option<f32>,
+        min_max: Option<&MinMaxStats>,
+    ) -> f32 {
+        let virtual_count = self.virtual_count.load(Relaxed);
+        let count = self.count() + virtual_count;
+        let amaf = config
+            .rave
+            .as_ref()
+            .zip(self.amaf.as_ref())
+            .filter(|(_, amaf)| amaf.count() > 0);
+        let quality = match (count, amaf, first_play_urgency) {
+            (0, Some((_, amaf)), _) => amaf.quality(),
+            (0, None, Some(first_play_urgency)) => first_play_urgency,
+            (0, None, None) => return f32::INFINITY,
+            _ => {
+                let quality = (self.total_value() - config.virtual_loss * virtual_count as f32)
+                    / count as f32;
+                match amaf {
+                    Some((rave, amaf)) => {
+                        let beta = rave.beta(count);
+                        (1.0 - beta) * quality + beta * amaf.quality()
+                    }
+                    None => quality,
+                }
+            }
+        };
+        let quality = min_max.map_or(quality, |min_max| min_max.normalize(quality));
+        let explore_factor = config.explore_factor(visit_count);
+        quality + explore_factor * self.prior * (visit_count as f32).sqrt() / (1.0 + count as f32)
     }
-}
 
-unsafe impl<const A: usize> Array for Stats<A> {
-    type Item = Stats<A>;
+    fn add_virtual_loss(&self) {
+        self.virtual_count.fetch_add(1, Relaxed);
+    }
 
-    fn size() -> usize {
-        A
+    /// record a value backed up through the action, replacing the virtual loss of the descent
+    fn record(&self, value: f32) {
+        self.count.fetch_add(1, Relaxed);
+        self.total_value.add(value);
+        self.virtual_count.fetch_sub(1, Relaxed);
     }
 }
 
 pub struct Node<
     const N: usize,
-    const D: usize,
-    S: State<D>,
+    S: State,
 > {
     state: S,
     /// N(s): The number of times state s has been visited
-    visit_count: usize,
-    reward: OnceCell<Option<f32>>,
-    actions: OnceCell<ActionMap<NodeRef<N>>>,
-    action_stats: OnceCell<ActionMap<Stats<N>>>,
+    visit_count: AtomicUsize,
+    /// rewards of a terminal state for every player
+    outcome: OnceLock<Option<Vec<f32>>>,
+    action_stats: OnceLock<ActionMap<Stats<N>>>,
+    /// V(s): value of the state from its evaluation
+    value: OnceLock<f32>,
+    /// W(s, o) of every player for each chance outcome o, outcome by outcome, none unless
+    /// chance moves next, the actions being the chance outcomes
+    chance: Option<Box<[AtomicF32]>>,
+    /// values of a non-terminal state for every player once proven from its actions
+    proven: OnceLock<Vec<f32>>,
 }
 
 impl<
     const N: usize,
-    const D: usize,
-    S: State<D>,
-> Node<N, D, S> {
+    S: State,
+> Node<N, S> {
+    /// node of the state, a chance node having its outcomes as actions right away, with their
+    /// probabilities as priors
     pub fn new(state: S) -> Self {
-        Self {
+        let outcomes = state.chance_outcomes();
+        let player_count = state.player_count();
+        let node = Self {
             state,
-            visit_count: 0,
-            reward: OnceCell::new(),
-            actions: OnceCell::new(),
-            action_stats: OnceCell::new(),
+            visit_count: AtomicUsize::new(0),
+            outcome: OnceLock::new(),
+            action_stats: OnceLock::new(),
+            value: OnceLock::new(),
+            chance: outcomes.as_ref().map(|probabilities| {
+                let len = probabilities.len() * player_count;
+                (0..len).map(|_| AtomicF32::default()).collect()
+            }),
+            proven: OnceLock::new(),
+        };
+        if let Some(probabilities) = outcomes {
+            node.init_actions(ActionMap::new(probabilities.into_iter().map(F32)), None);
         }
+        node
+    }
+
+    /// whether chance moves next
+    pub fn is_chance(&self) -> bool {
+        self.chance.is_some()
+    }
+
+    pub fn state(&self) -> &S {
+        &self.state
     }
 
     /// the number of times this state has been visited
     pub fn visit_count(&self) -> usize {
-        self.visit_count
+        // acquire pairs with the release by the backup, so the actions of a visited node are seen
+        self.visit_count.load(Acquire)
+    }
+
+    /// whether the game is over
+    pub fn is_terminal(&self) -> bool {
+        self.state.is_terminal()
+    }
+
+    /// the reward for this state for the player to move, none if non-terminal state
+    pub fn reward(&self) -> Option<f32> {
+        self.outcome().map(|outcome| outcome[self.state.to_play()])
+    }
+
+    /// the rewards for this state for every player, none if non-terminal state
+    pub fn outcome(&self) -> Option<&[f32]> {
+        self.outcome
+            .get_or_init(|| {
+                self.is_terminal()
+                    .then(|| self.state.outcome().expect("terminal state should have an outcome"))
+            })
+            .as_deref()
+    }
+
+    /// V(s) from the evaluator for the player to move, none until the state is expanded
+    pub fn value(&self) -> Option<f32> {
+        self.value.get().copied()
+    }
+
+    /// value of the state for the player to move once proven, the reward for a terminal state
+    pub fn proven_value(&self) -> Option<f32> {
+        self.proven_values().map(|values| values[self.state.to_play()])
+    }
+
+    /// values of the state for every player once proven, the outcome for a terminal state
+    pub fn proven_values(&self) -> Option<&[f32]> {
+        self.outcome().or_else(|| self.proven.get().map(Vec::as_slice))
     }
 
-    /// the reward for this state, none if non-terminal state
-    pub fn reward(&self) -> &Option<f32> {
-        self.reward.get_or_init(|| self.state.reward())
+    /// whether the state is a proven win, loss or draw for the player to move
+    pub fn proven(&self) -> Option<Proven> {
+        self.proven_value().map(Proven::of)
     }
 
     pub fn has_actions(&self) -> bool {
         self.action_stats.get().is_some()
     }
 
-    /// initialize the actions
-    pub fn init_actions(&self, mut new_node_ref: impl FnMut() -> NodeRef<N>) {
-        self.actions.get_or_init(|| {
-            let prior = 1.0 / self.state.action_count() as f32;
-            let (child_refs, stats): (Vec<_>, Vec<_>) = self
-                .state
-                .action_iter()
-                .map(|_action| {
-                    (
-                        new_node_ref(),
-                        Stats {
-                            count: 0,
-                            total_value: 0.0,
-                            prior,
-                        },
-                    )
-                })
-                .unzip();
-            self.action_stats.get_or_init(|| ActionMap::new(stats));
-            ActionMap::new(child_refs)
+    /// initialize the actions with their prior probabilities, and their `State::action_id` when
+    /// keeping all-moves-as-first statistics
+    pub fn init_actions(&self, priors: ActionMap<F32<N>>, amaf_keys: Option<Vec<usize>>) {
+        self.action_stats.get_or_init(|| {
+            let mut amaf_keys = amaf_keys.map(|amaf_keys| amaf_keys.into_iter());
+            ActionMap::new(priors.iter().map(|prior| {
+                let amaf_key = amaf_keys.as_mut().and_then(|amaf_keys| amaf_keys.next());
+                Stats::new(prior.0, amaf_key)
+            }))
         });
     }
 
-    /// get the actions
-    fn actions(&self) -> &ActionMap<NodeRef<N>> {
-        self.actions.get().expect("actions should be initialized")
+    /// number of actions, zero until initialized
+    pub fn action_count(&self) -> usize {
+        self.action_stats.get().map_or(0, |action_stats| action_stats.len())
     }
 
     /// get the action stats
@@ -256,153 +878,1692 @@ impl<
             .expect("action stats should be created")
     }
 
-    /// select the action to take
-    pub fn select_action(&self, explore_factor: f32) -> (Action, NodeRef<N>) {
+    /// action achieving the proven value of a proven state, if any
+    fn winning_action(&self) -> Option<Action> {
+        let value = self.proven.get()?[self.state.to_play()];
+        self.action_stats()
+            .action_value_iter()
+            .find(|(_, stats)| stats.proven_value() == Some(value))
+            .map(|(action, _)| action)
+    }
+
+    /// select the action to take, the best action of a proven state right away and never a
+    /// proven action beaten by another proven one, with Q(s, a) of the actions not yet taken from
+    /// the configured first play urgency, normalizing Q(s, a) by the bounds when given
+    pub fn select_action(&self, config: &SearchConfig, min_max: Option<&MinMaxStats>) -> Action {
+        if let Some(action) = self.winning_action() {
+            return action;
+        }
+        let first_play_urgency = self.first_play_urgency(config, min_max);
+        let best_proven = self
+            .action_stats()
+            .iter()
+            .filter_map(Stats::proven_value)
+            .max_by_key(|value| OrderedFloat(*value));
         let (action, _) = self
             .action_stats()
             .action_value_iter()
-            .map(|(action, stats)| (action, stats.puct(self.visit_count(), explore_factor)))
-            .max_by_key(|(_, puct)| OrderedFloat(*puct))
+            .map(|(action, stats)| {
+                let puct = stats.puct(self.visit_count(), config, first_play_urgency, min_max);
+                let beaten = stats
+                    .proven_value()
+                    .zip(best_proven)
+                    .is_some_and(|(value, best)| value < best);
+                (action, (!beaten, OrderedFloat(puct)))
+            })
+            .max_by_key(|(_, key)| *key)
             .expect("at least one action");
-        (action, self.actions()[action])
+        action
+    }
+
+    /// Q(s, a) of the actions not yet taken, none for infinite, a loss and a win being the
+    /// bounds of Q(s, a) in the tree when normalizing it
+    fn first_play_urgency(
+        &self,
+        config: &SearchConfig,
+        min_max: Option<&MinMaxStats>,
+    ) -> Option<f32> {
+        // Q(s) from the visits so far, or V(s) from the evaluation before any
+        let quality = self.quality().or(self.value()).unwrap_or(0.0);
+        let (loss, win) = min_max.and_then(MinMaxStats::bounds).unwrap_or((-1.0, 1.0));
+        config.first_play_urgency.quality(quality, loss, win)
     }
 
     /// pi_s(a) is probability that action `a` is taken from state `s`
     pub fn action_probability(&self, temperature: f32) -> ActionMap<F32<N>> {
-        ActionMap::new(self.action_stats().probability_iter(self.visit_count(), temperature))
+        ActionMap::new(self.action_stats().probability_iter(temperature))
+    }
+
+    /// sample action, the best action of a proven state, or a chance outcome by its probability when
+    /// chance moves next
+    pub fn sample_action(&self, temperature: f32, rng: &mut impl Rng) -> Action {
+        if self.is_chance() {
+            return self.sample_outcome(rng);
+        }
+        if let Some(action) = self.winning_action() {
+            return action;
+        }
+        let probabilities = self.action_probability(temperature);
+        sample(probabilities.action_value_iter().map(|(action, p)| (action, p.0)), rng)
     }
 
-    /// sample action
-    pub fn sample_action(&self, temperature: f32) -> (Action, NodeRef<N>) {
-        let r = random_range(0.0f32..1.0);
-        let mut p = 0.0;
-        let action = self
-            .action_probability(temperature)
+    /// sample a chance outcome by its probability
+    pub fn sample_outcome(&self, rng: &mut impl Rng) -> Action {
+        let probabilities = self
+            .action_stats()
             .action_value_iter()
-            .find_or_last(|(_action, prob)| {
-                p += prob.0;
-                p >= r
-            })
-            .expect("at least one action")
-            .0;
-        (action, self.actions()[action])
+            .map(|(action, stats)| (action, stats.prior()));
+        sample(probabilities, rng)
     }
 
-    /// mean quality of state
-    pub fn quality(&self) -> f32 {
-        self.action_stats().quality()
+    /// action of a player by PUCT or a chance outcome by sampling it
+    fn descend_action(
+        &self,
+        config: &SearchConfig,
+        min_max: Option<&MinMaxStats>,
+        rng: &mut impl Rng,
+    ) -> Action {
+        match self.is_chance() {
+            true => self.sample_outcome(rng),
+            false => self.select_action(config, min_max),
+        }
+    }
+
+    /// values of every player at a chance node, sum_o p(o) * W(s, o) / N(s, o) / sum_o p(o) over
+    /// the outcomes o sampled so far
+    fn expected_values(&self) -> Option<Vec<f32>> {
+        let totals = self.chance.as_ref()?;
+        let player_count = self.state.player_count();
+        let mut probability = 0.0;
+        let mut values = vec![0.0; player_count];
+        for (stats, totals) in self.action_stats().iter().zip(totals.chunks(player_count)) {
+            let count = stats.count();
+            if count > 0 {
+                probability += stats.prior();
+                for (value, total) in values.iter_mut().zip(totals) {
+                    *value += stats.prior() * total.load() / count as f32;
+                }
+            }
+        }
+        (probability > 0.0).then(|| values.into_iter().map(|value| value / probability).collect())
+    }
+
+    /// record the values of every player backed up through a chance outcome
+    fn record_outcome(&self, outcome: Action, values: &[f32]) {
+        if let Some(totals) = &self.chance {
+            let player_count = self.state.player_count();
+            totals[outcome.index() * player_count..]
+                .iter()
+                .zip(values)
+                .for_each(|(total, value)| total.add(*value));
+        }
+    }
+
+    /// Q(s): mean value of the state over the visits of its actions, none before any
+    pub fn quality(&self) -> Option<f32> {
+        self.action_stats.get().and_then(|action_stats| action_stats.quality())
+    }
+
+    /// mix Dirichlet noise into the priors to encourage exploration from the root
+    pub fn add_noise(&mut self, noise: &NoiseConfig, rng: &mut impl Rng) {
+        let gamma = Gamma::new(noise.alpha, 1.0).expect("alpha should be positive");
+        let action_stats = self
+            .action_stats
+            .get_mut()
+            .expect("action stats should be created");
+        // Dirichlet sample is independent Gamma(alpha, 1) samples normalized to sum to one
+        let samples = action_stats.iter().map(|_| gamma.sample(rng)).collect_vec();
+        let total = samples.iter().sum::<f32>();
+        if total <= 0.0 {
+            return;
+        }
+        action_stats
+            .iter_mut()
+            .zip(samples)
+            .for_each(|(stats, sample)| {
+                stats.prior = (1.0 - noise.epsilon) * stats.prior + noise.epsilon * sample / total
+            })
     }
 }
 
+/// action drawn with the given probabilities, the last one if they sum to less than the draw
+fn sample(probabilities: impl Iterator<Item = (Action, f32)>, rng: &mut impl Rng) -> Action {
+    let r = rng.random_range(0.0f32..1.0);
+    let mut p = 0.0;
+    probabilities
+        .find_or_last(|(_action, prob)| {
+            p += prob;
+            p >= r
+        })
+        .expect("at least one action")
+        .0
+}
+
+/// search tree over states S, N being the size of their action space `State::ACTION_SPACE`
 pub struct Tree<
     const N: usize,
-    const D: usize,
-    S: State<D>,
+    S: State,
 > {
     root_ref: NodeRef<N>,
-    nodes: Nodes<N, D, S>,
+    nodes: Nodes<N, S>,
+    config: SearchConfig,
+    rng: StdRng,
 }
 
 impl<
     const N: usize,
-    const D: usize,
-    S: State<D>,
-> Default for Tree<N, D, S> {
+    S: State,
+> Default for Tree<N, S> {
     fn default() -> Self {
-        Self::new()
+        Self::new(SearchConfig::new())
     }
 }
 
 impl<
     const N: usize,
-    const D: usize,
-    S: State<D>,
-> Tree<N, D, S> {
-    pub fn new() -> Self {
-        let (root_ref, nodes) = Nodes::<N, D, S>::new();
-        Self { root_ref, nodes }
+    S: State,
+> Tree<N, S> {
+    /// tree seeded by the configured seed, or by the OS without one
+    pub fn new(config: SearchConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+        Self::with_rng(config, rng)
     }
 
-    pub fn simulate(&mut self, node_ref: NodeRef<N>, count: usize) {
-        let mut back = Vec::with_capacity(128);
-        for _ in 0..count {
-            let mut curr_ref = node_ref;
-            while self.nodes[curr_ref].visit_count() > 0 && self.nodes[curr_ref].reward().is_none()
-            {
-                let (action, new_curr_ref) = self.nodes.select_action(curr_ref);
-                back.push((curr_ref, action));
-                curr_ref = new_curr_ref;
-            }
-            let mut value = match self.nodes[curr_ref].reward() {
-                Some(reward) => {
-                    // If it is a terminal state, adjudicate it, and set the value V(s) to the game
-                    // outcome (player one wins = +1, draw = +0.01, player two wins = -1) for a
-                    // two-player game with player one taking action a — (draws get assigned a
-                    // small positive value); recall that V(s) => -V(s) for the competing agent
-                    // (player two’s values are the negative of player one’s values) if your
-                    // situation is a zero-sum game
-                    *reward
-                }
-                None => {
-                    // If you have not visited it yet, and it’s not a terminal state, call this a
-                    // leaf state. Send the leaf state into the neural net, and initialize the
-                    // state’s prior probabilities P(s, a) and value V(s) to whatever the neural net
-                    // outputs, adding Dirichlet noise to P(s, a) if s is a root state.
-                    // first visit of non-terminal
-                    0.0
-                }
-            };
-            // Back-propagate the value V(s) of the leaf or terminal state to the state that
-            // led to it, being careful with minus signs; increment the number of times each
-            // state-action pair N(s, a) was visited along the path from the root node to the
-            // leaf or terminal node; adjust N(s), W(s, a), and Q(s, a) for all the states and
-            // actions along this path
-            while let Some((prev_ref, action)) = back.pop() {
-                let prev = &mut self.nodes[prev_ref];
-                prev.visit_count += 1;
-                value = prev.state.value(action, value);
-                let prev_action_stats = &mut prev
-                    .action_stats
-                    .get_mut()
-                    .expect("action results should be expanded")[action];
-                prev_action_stats.count += 1;
-                prev_action_stats.total_value += value
-            }
-        }
-    }
-
-    /// execute sim_count simulations
-    pub fn execute_episode(
-        &mut self,
-        sim_count: usize,
-        temperature: f32
-        /* Neural Net */
-    ) -> impl Iterator<Item = Example<N, D>> + '_ {
-        let mut cur_ref = self.root_ref;
-        let mut back = Vec::new();
-        loop {
-            self.simulate(cur_ref, sim_count);
-            let (action, new_cur_ref) = self.nodes.sample_action(cur_ref, temperature);
-            back.push((cur_ref, action));
-            cur_ref = new_cur_ref;
-            if let Some(reward) = self.nodes[cur_ref].reward() {
-                // stop at terminal state
-                let mut value = *reward;
-                return back.into_iter().map(move |(node_ref, action)| {
-                    let node = &self.nodes[node_ref];
-                    value = node.state.value(action, value);
-                    let state = node.state.as_array();
-                    let pi = node.action_probability(temperature);
-                    Example{state, pi, value}
-                })
-            }
+    /// tree whose root noise and sampled actions are reproducible from the seed
+    pub fn with_seed(config: SearchConfig, seed: u64) -> Self {
+        Self::with_rng(config, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(config: SearchConfig, rng: StdRng) -> Self {
+        assert_eq!(N, S::ACTION_SPACE, "N should be the size of the action space");
+        let (root_ref, nodes) = Nodes::<N, S>::new(&config);
+        Self {
+            root_ref,
+            nodes,
+            config,
+            rng,
         }
     }
-}
 
-/// training example
-pub struct Example<const N: usize, const D: usize, > {
-    pub state: [[i32; D]; D],
-    pub pi: ActionMap<F32<N>>,
-    pub value: f32,
-}
\ No newline at end of file
+    pub fn config(&self) -> &SearchConfig {
+        &self.config
+    }
+
+    pub fn root_ref(&self) -> NodeRef<N> {
+        self.root_ref
+    }
+
+    pub fn root(&self) -> &Node<N, S> {
+        &self.nodes[self.root_ref]
+    }
+
+    pub fn nodes(&self) -> &Nodes<N, S> {
+        &self.nodes
+    }
+
+    /// sample an action from the root with the tree's random number generator, a chance
+    /// outcome by its probability when chance moves next
+    pub fn sample_action(&mut self, temperature: f32) -> Action {
+        self.nodes
+            .sample_action(self.root_ref, temperature, &mut self.rng)
+    }
+
+    /// sample a chance outcome of the root by its probability with the tree's random number
+    /// generator
+    pub fn sample_outcome(&mut self) -> Action {
+        self.nodes[self.root_ref].sample_outcome(&mut self.rng)
+    }
+
+    /// whether the root is a proven win, loss or draw for the player to move
+    pub fn proven(&self) -> Option<Proven> {
+        self.root().proven()
+    }
+
+    /// take the action from the root, making its child the root while keeping the statistics
+    /// of the child's subtree, freeing the rest of the tree, compacting the arena once it is
+    /// mostly free slots, and returning the previous root
+    pub fn advance(&mut self, action: Action) -> Node<N, S> {
+        let root_ref = self.root_ref;
+        if !self.nodes.is_open(root_ref) {
+            // nothing searched yet, start over from the child
+            let state = self.nodes[root_ref].state.take(action);
+            let (new_root_ref, nodes) = Nodes::with_root(state, &self.config);
+            self.root_ref = new_root_ref;
+            return std::mem::replace(&mut self.nodes, nodes)
+                .nodes
+                .into_iter()
+                .nth(root_ref.index())
+                .flatten()
+                .expect("root should exist");
+        }
+        let child_ref = self.nodes.child(root_ref, action);
+        let root = self
+            .nodes
+            .sweep(&[child_ref])
+            .into_iter()
+            .find(|(node_ref, _)| *node_ref == root_ref)
+            .expect("root should not be in the subtree of its child")
+            .1;
+        self.root_ref = self.nodes.compact(&[child_ref])[0];
+        root
+    }
+
+    /// with pruning configured, prune the least visited subtrees once the arena is full, keeping
+    /// the node being searched from
+    fn enforce_node_budget(&mut self, node_ref: NodeRef<N>) {
+        let Some(NodeBudgetConfig {
+            max_nodes,
+            prune_to: Some(prune_to),
+        }) = self.config.node_budget
+        else {
+            return;
+        };
+        if self.nodes.len() >= max_nodes {
+            let keep = (max_nodes as f32 * prune_to) as usize;
+            self.nodes.prune(self.root_ref, &[node_ref], keep);
+        }
+    }
+
+    /// whether the node budget is enforced by pruning
+    fn prunes(&self) -> bool {
+        self.config
+            .node_budget
+            .as_ref()
+            .is_some_and(|budget| budget.prune_to.is_some())
+    }
+
+    /// execute count simulations from the node, evaluating up to the configured batch size of
+    /// leaves together
+    pub fn simulate(
+        &mut self,
+        node_ref: NodeRef<N>,
+        count: usize,
+        evaluator: &impl Evaluator<N, S>,
+    ) {
+        self.simulate_through(node_ref, None, count, evaluator)
+    }
+
+    /// execute count simulations from the node that all take the given action first
+    pub fn simulate_action(
+        &mut self,
+        node_ref: NodeRef<N>,
+        action: Action,
+        count: usize,
+        evaluator: &impl Evaluator<N, S>,
+    ) {
+        self.simulate_through(node_ref, Some(action), count, evaluator)
+    }
+
+    fn simulate_through(
+        &mut self,
+        node_ref: NodeRef<N>,
+        action: Option<Action>,
+        count: usize,
+        evaluator: &impl Evaluator<N, S>,
+    ) {
+        let batch_size = self.config.batch_size.max(1);
+        let mut done = 0;
+        while done < count {
+            let batch_count = batch_size.min(count - done);
+            self.enforce_node_budget(node_ref);
+            done += self.simulate_batch(node_ref, action, batch_count, evaluator);
+        }
+    }
+
+    /// search from the root until the deadline, or until the most visited root action can no
+    /// longer change, returning the number of simulations executed
+    pub fn search_until(
+        &mut self,
+        deadline: Instant,
+        evaluator: &impl Evaluator<N, S>,
+    ) -> usize {
+        self.search(None, Some(deadline), None, evaluator)
+    }
+
+    /// search from the root until the tree holds max_nodes nodes, max_time has passed or
+    /// max_sims simulations are executed, whichever comes first, or until the most visited root
+    /// action can no longer change, returning the number of simulations executed. Without any
+    /// limit the search executes the configured sim_count
+    pub fn search_with_budget(
+        &mut self,
+        max_nodes: Option<usize>,
+        max_time: Option<Duration>,
+        max_sims: Option<usize>,
+        evaluator: &impl Evaluator<N, S>,
+    ) -> usize {
+        let deadline = max_time.map(|max_time| Instant::now() + max_time);
+        let max_sims = match (max_nodes, max_time, max_sims) {
+            (None, None, None) => Some(self.config.sim_count),
+            _ => max_sims,
+        };
+        self.search(max_nodes, deadline, max_sims, evaluator)
+    }
+
+    fn search(
+        &mut self,
+        max_nodes: Option<usize>,
+        deadline: Option<Instant>,
+        max_sims: Option<usize>,
+        evaluator: &impl Evaluator<N, S>,
+    ) -> usize {
+        let start = Instant::now();
+        let root_ref = self.root_ref;
+        let batch_size = self.config.batch_size.max(1);
+        let mut done = 0;
+        loop {
+            let now = Instant::now();
+            let remaining_sims = max_sims.map(|max_sims| max_sims.saturating_sub(done));
+            if remaining_sims == Some(0)
+                || max_nodes.is_some_and(|max_nodes| self.nodes.len() >= max_nodes)
+                || deadline.is_some_and(|deadline| now >= deadline)
+                || self.root().proven_value().is_some()
+            {
+                break;
+            }
+            // simulations left before the deadline at the rate so far
+            let remaining_time_sims = deadline.filter(|_| done > 0).map(|deadline| {
+                let rate = done as f64 / start.elapsed().as_secs_f64().max(f64::EPSILON);
+                (deadline.duration_since(now).as_secs_f64() * rate).ceil() as usize
+            });
+            let remaining = remaining_sims.into_iter().chain(remaining_time_sims).min();
+            if remaining.is_some_and(|remaining| self.visit_lead() > remaining) {
+                break;
+            }
+            let count = batch_size.min(remaining_sims.unwrap_or(batch_size));
+            self.enforce_node_budget(root_ref);
+            done += self.simulate_batch(root_ref, None, count, evaluator);
+        }
+        done
+    }
+
+    /// visits of the most visited root action beyond those of the next most visited, unbounded
+    /// with a single action
+    fn visit_lead(&self) -> usize {
+        let root = self.root();
+        match root.action_count() {
+            0 => 0,
+            1 => usize::MAX,
+            _ => {
+                let counts = root
+                    .action_stats()
+                    .iter()
+                    .map(|stats| stats.count())
+                    .sorted_unstable_by(|a, b| b.cmp(a))
+                    .collect_vec();
+                counts[0] - counts[1]
+            }
+        }
+    }
+
+    /// descend count times from the node, the virtual loss of each descent steering the next
+    /// ones down other paths, then evaluate the distinct leaves reached as one batch and back up
+    /// every path, returning the number of simulations executed, fewer than count when the arena
+    /// fills up and pruning has to make room before the next batch
+    fn simulate_batch(
+        &mut self,
+        node_ref: NodeRef<N>,
+        action: Option<Action>,
+        count: usize,
+        evaluator: &impl Evaluator<N, S>,
+    ) -> usize {
+        let mut pending = Vec::with_capacity(count);
+        let mut leaf_refs = Vec::with_capacity(count);
+        let mut full = false;
+        let mut done = 0;
+        while done < count && !full {
+            done += 1;
+            let mut back = Vec::with_capacity(128);
+            let mut curr_ref = node_ref;
+            let mut first_action = action;
+            // chance nodes are passed through to one of their outcomes from the first visit
+            while (self.nodes[curr_ref].visit_count() > 0 || self.nodes[curr_ref].is_chance())
+                && self.nodes[curr_ref].proven_value().is_none()
+            {
+                let action = first_action.take().unwrap_or_else(|| {
+                    let min_max = self.nodes.min_max.as_ref();
+                    self.nodes[curr_ref].descend_action(&self.config, min_max, &mut self.rng)
+                });
+                if !self.nodes.has_room(curr_ref, action) {
+                    // out of nodes, evaluate the state again instead of expanding the tree, and
+                    // end the batch here when pruning will make room for the next one
+                    full = self.prunes();
+                    break;
+                }
+                back.push((curr_ref, action));
+                curr_ref = self.nodes.take_action(curr_ref, action);
+            }
+            match self.nodes[curr_ref].proven_values() {
+                Some(values) => {
+                    // If it is a terminal state, adjudicate it, backing up its outcome, the value
+                    // of the state for every player. A state already proven by the search needs no
+                    // further search either, and is backed up with its proven values
+                    self.nodes.backup(curr_ref, &mut back, values.to_vec(), &[])
+                }
+                None => {
+                    // If you have not visited it yet, and it’s not a terminal state, call this a
+                    // leaf state. Send the leaf state into the neural net, and initialize the
+                    // state’s prior probabilities P(s, a) and value V(s) to whatever the neural net
+                    // outputs, adding Dirichlet noise to P(s, a) if s is a root state.
+                    // first visit of non-terminal, evaluated with the rest of the batch
+                    if !leaf_refs.contains(&curr_ref) {
+                        leaf_refs.push(curr_ref);
+                    }
+                    pending.push((curr_ref, back));
+                }
+            }
+        }
+        let states = leaf_refs
+            .iter()
+            .map(|leaf_ref| &self.nodes[*leaf_ref].state)
+            .collect_vec();
+        let evaluations = evaluator
+            .evaluate_batch(&states)
+            .into_iter()
+            .zip(&leaf_refs)
+            .map(|(evaluation, leaf_ref)| {
+                let values = evaluation.value.clone();
+                let action_ids = evaluation.action_ids.clone();
+                self.nodes.expand(*leaf_ref, evaluation);
+                (*leaf_ref, (values, action_ids))
+            })
+            .collect::<HashMap<_, _>>();
+        for (leaf_ref, mut back) in pending {
+            let (values, action_ids) = &evaluations[&leaf_ref];
+            self.nodes.backup(leaf_ref, &mut back, values.clone(), action_ids);
+        }
+        done
+    }
+
+    /// play a game from the root, executing the configured simulations before each move, with
+    /// Dirichlet noise added to the priors of each node as it becomes the search root unless
+    /// noise is configured off, e.g. for evaluation or arena play, or with the Gumbel root search
+    /// when configured
+    pub fn execute_episode(
+        &mut self,
+        evaluator: &impl Evaluator<N, S>,
+    ) -> impl Iterator<Item = Example<N>> {
+        self.play_episode(|tree, node_ref, action, count| {
+            tree.simulate_through(node_ref, action, count, evaluator)
+        })
+        .into_iter()
+    }
+
+    /// play a game with search(tree, node, action, count) executing count simulations from the
+    /// node, all taking the action first if given
+    fn play_episode(
+        &mut self,
+        mut search: impl FnMut(&mut Self, NodeRef<N>, Option<Action>, usize),
+    ) -> Vec<Example<N>> {
+        let mut back = Vec::new();
+        loop {
+            let cur_ref = self.root_ref;
+            let (action, pi) = match self.config.gumbel.clone() {
+                // chance moves at the root without search, leaving no example to learn from
+                _ if self.root().is_chance() => (self.sample_outcome(), None),
+                Some(gumbel) => {
+                    let (action, pi) = self.gumbel_search(&gumbel, &mut search);
+                    (action, Some(pi))
+                }
+                None => {
+                    let temperature = self.config.temperature(back.len());
+                    let mut sim_count = self.config.sim_count.max(1);
+                    if let Some(noise) = self.config.noise.clone() {
+                        if !self.nodes.is_open(cur_ref) {
+                            // expand the root so it has priors to add noise to
+                            search(self, cur_ref, None, 1);
+                            sim_count -= 1;
+                        }
+                        self.nodes[cur_ref].add_noise(&noise, &mut self.rng);
+                    }
+                    search(self, cur_ref, None, sim_count);
+                    let action = self.nodes.sample_action(cur_ref, temperature, &mut self.rng);
+                    (action, Some(self.nodes[cur_ref].action_probability(temperature)))
+                }
+            };
+            back.push((self.advance(action), action, pi));
+            if self.root().is_terminal() {
+                // stop at terminal state, each example valued by the reward of the player to move
+                let outcome = self.root().outcome().expect("terminal state should have an outcome");
+                return back
+                    .into_iter()
+                    .filter_map(|(node, _, pi)| {
+                        let value = outcome[node.state.to_play()];
+                        let mut global_pi = [0.0; N];
+                        for (action, probability) in pi?.action_value_iter() {
+                            global_pi[node.state.action_id(action)] = probability.0;
+                        }
+                        let mut legal = [false; N];
+                        legal.copy_from_slice(&node.state.legal_actions());
+                        let state = node.state.observation();
+                        Some(Example { state, pi: global_pi, legal, value })
+                    })
+                    .collect();
+            }
+        }
+    }
+}
+
+impl<
+    const N: usize,
+    S: State + Clone + Send + Sync,
+> Tree<N, S> {
+    /// execute count simulations from the node on the configured number of threads
+    pub fn simulate_parallel(
+        &mut self,
+        node_ref: NodeRef<N>,
+        count: usize,
+        evaluator: &(impl Evaluator<N, S> + Sync),
+    ) {
+        self.simulate_parallel_through(node_ref, None, count, evaluator)
+    }
+
+    /// simulate_action on the configured number of threads
+    pub fn simulate_action_parallel(
+        &mut self,
+        node_ref: NodeRef<N>,
+        action: Action,
+        count: usize,
+        evaluator: &(impl Evaluator<N, S> + Sync),
+    ) {
+        self.simulate_parallel_through(node_ref, Some(action), count, evaluator)
+    }
+
+    fn simulate_parallel_through(
+        &mut self,
+        node_ref: NodeRef<N>,
+        action: Option<Action>,
+        count: usize,
+        evaluator: &(impl Evaluator<N, S> + Sync),
+    ) {
+        if self.config.threads <= 1 {
+            return self.simulate_through(node_ref, action, count, evaluator);
+        }
+        self.enforce_node_budget(node_ref);
+        let nodes = RwLock::new(std::mem::take(&mut self.nodes));
+        let started = AtomicUsize::new(0);
+        let config = &self.config;
+        // a generator of each thread for the chance outcomes, seeded from the tree's
+        let seeds = (0..config.threads).map(|_| self.rng.random()).collect_vec();
+        let batch_size = config.batch_size.max(1);
+        thread::scope(|scope| {
+            for seed in seeds {
+                let (nodes, started) = (&nodes, &started);
+                scope.spawn(move || {
+                    let mut rng = StdRng::seed_from_u64(seed);
+                    // each worker claims a batch of the simulations at a time
+                    loop {
+                        let start = started.fetch_add(batch_size, Relaxed);
+                        if start >= count {
+                            break;
+                        }
+                        let batch_count = batch_size.min(count - start);
+                        Self::simulate_shared(
+                            nodes, node_ref, action, config, evaluator, batch_count, &mut rng,
+                        )
+                    }
+                });
+            }
+        });
+        self.nodes = nodes.into_inner().expect("nodes lock should not be poisoned");
+    }
+
+    /// execute_episode with the simulations before each move on the configured number of threads
+    pub fn execute_episode_parallel(
+        &mut self,
+        evaluator: &(impl Evaluator<N, S> + Sync),
+    ) -> impl Iterator<Item = Example<N>> {
+        self.play_episode(|tree, node_ref, action, count| {
+            tree.simulate_parallel_through(node_ref, action, count, evaluator)
+        })
+        .into_iter()
+    }
+
+    /// a batch of count simulations concurrent with those of the other workers, descending and
+    /// backing up under the read lock while the statistics are updated atomically, taking the
+    /// write lock only to add a child, and evaluating clones of the distinct leaf states reached
+    /// as one batch without holding the lock
+    fn simulate_shared(
+        nodes: &RwLock<Nodes<N, S>>,
+        node_ref: NodeRef<N>,
+        first_action: Option<Action>,
+        config: &SearchConfig,
+        evaluator: &impl Evaluator<N, S>,
+        count: usize,
+        rng: &mut StdRng,
+    ) {
+        let read = || nodes.read().expect("nodes lock should not be poisoned");
+        let mut pending = Vec::with_capacity(count);
+        let mut leaves = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut back = Vec::with_capacity(128);
+            let (leaf_ref, leaf) =
+                Self::descend_shared(nodes, node_ref, first_action, config, &mut back, rng);
+            match leaf {
+                Either::Left(values) => read().backup(leaf_ref, &mut back, values, &[]),
+                Either::Right(state) => {
+                    if !leaves.iter().any(|(curr_ref, _)| *curr_ref == leaf_ref) {
+                        leaves.push((leaf_ref, state));
+                    }
+                    pending.push((leaf_ref, back));
+                }
+            }
+        }
+        let states = leaves.iter().map(|(_, state)| state).collect_vec();
+        let evaluations = evaluator
+            .evaluate_batch(&states)
+            .into_iter()
+            .zip(&leaves)
+            .map(|(evaluation, (leaf_ref, _))| {
+                let values = evaluation.value.clone();
+                let action_ids = evaluation.action_ids.clone();
+                read().expand(*leaf_ref, evaluation);
+                (*leaf_ref, (values, action_ids))
+            })
+            .collect::<HashMap<_, _>>();
+        for (leaf_ref, mut back) in pending {
+            let (values, action_ids) = &evaluations[&leaf_ref];
+            read().backup(leaf_ref, &mut back, values.clone(), action_ids);
+        }
+    }
+
+    /// descend from the node under the read lock to a leaf, returning it with its proven values,
+    /// or with a clone of its state to evaluate
+    fn descend_shared(
+        nodes: &RwLock<Nodes<N, S>>,
+        node_ref: NodeRef<N>,
+        first_action: Option<Action>,
+        config: &SearchConfig,
+        back: &mut Vec<(NodeRef<N>, Action)>,
+        rng: &mut StdRng,
+    ) -> (NodeRef<N>, Either<Vec<f32>, S>) {
+        let read = || nodes.read().expect("nodes lock should not be poisoned");
+        let mut curr_ref = node_ref;
+        let mut first_action = first_action;
+        let leaf = loop {
+            let guard = read();
+            let curr = &guard[curr_ref];
+            if let Some(values) = curr.proven_values() {
+                break Either::Left(values.to_vec());
+            }
+            if curr.visit_count() == 0 && !curr.is_chance() {
+                break Either::Right(curr.state.clone());
+            }
+            let action = first_action
+                .take()
+                .unwrap_or_else(|| curr.descend_action(config, guard.min_max.as_ref(), rng));
+            if !guard.has_room(curr_ref, action) {
+                // out of nodes, evaluate the state again instead of expanding the tree
+                break Either::Right(curr.state.clone());
+            }
+            let stats = &curr.action_stats()[action];
+            stats.add_virtual_loss();
+            back.push((curr_ref, action));
+            curr_ref = match stats.child() {
+                Some(child_ref) => child_ref,
+                None => {
+                    drop(guard);
+                    let mut guard = nodes.write().expect("nodes lock should not be poisoned");
+                    guard.child(curr_ref, action)
+                }
+            };
+        };
+        (curr_ref, leaf)
+    }
+}
+
+/// training example
+pub struct Example<const N: usize, > {
+    /// observation of the state, `State::CHANNELS` x `State::HEIGHT` x `State::WIDTH` features
+    pub state: Vec<f32>,
+    /// search policy over the action space, indexed by `State::action_id` like the policy head
+    pub pi: [f32; N],
+    /// legal-action mask of the state over the action space
+    pub legal: [bool; N],
+    pub value: f32,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::FirstPlayUrgency;
+
+    /// pick one of three numbers, ending the game
+    struct Pick(Option<usize>);
+
+    impl State for Pick {
+        const ACTION_SPACE: usize = 3;
+        const HEIGHT: usize = 1;
+        const WIDTH: usize = 1;
+
+        fn init() -> Self {
+            Pick(None)
+        }
+
+        fn action_count(&self) -> usize {
+            3
+        }
+
+        fn take(&self, action: Action) -> Self {
+            Pick(Some(action.index()))
+        }
+
+        fn to_play(&self) -> usize {
+            self.0.map_or(0, |_| 1)
+        }
+
+        fn outcome(&self) -> Option<Vec<f32>> {
+            self.0.map(|_| vec![0.0; 2])
+        }
+
+        fn observation(&self) -> Vec<f32> {
+            vec![self.0.map_or(-1.0, |picked| picked as f32)]
+        }
+    }
+
+    /// pick a score of five, twenty or ten, the opponent losing as much
+    struct Score(Option<usize>);
+
+    impl State for Score {
+        const ACTION_SPACE: usize = 3;
+        const HEIGHT: usize = 1;
+        const WIDTH: usize = 1;
+
+        fn init() -> Self {
+            Score(None)
+        }
+
+        fn action_count(&self) -> usize {
+            if self.0.is_some() { 0 } else { 3 }
+        }
+
+        fn take(&self, action: Action) -> Self {
+            Score(Some([5, 20, 10][action.index()]))
+        }
+
+        fn to_play(&self) -> usize {
+            self.0.map_or(0, |_| 1)
+        }
+
+        fn outcome(&self) -> Option<Vec<f32>> {
+            self.0.map(|score| vec![score as f32, -(score as f32)])
+        }
+
+        fn observation(&self) -> Vec<f32> {
+            vec![self.0.map_or(0.0, |score| score as f32)]
+        }
+    }
+
+    /// take one or two from a pile of seven, taking the last one wins, with the pile and the
+    /// player to move
+    #[derive(Clone)]
+    struct Nim(usize, usize);
+
+    impl State for Nim {
+        const ACTION_SPACE: usize = 2;
+        const HEIGHT: usize = 1;
+        const WIDTH: usize = 2;
+
+        fn init() -> Self {
+            Nim(7, 0)
+        }
+
+        fn action_count(&self) -> usize {
+            self.0.min(2)
+        }
+
+        fn take(&self, action: Action) -> Self {
+            Nim(self.0 - action.index() - 1, 1 - self.1)
+        }
+
+        fn to_play(&self) -> usize {
+            self.1
+        }
+
+        fn outcome(&self) -> Option<Vec<f32>> {
+            // the player to move has lost
+            (self.0 == 0).then(|| self.values(-1.0))
+        }
+
+        fn observation(&self) -> Vec<f32> {
+            vec![self.0 as f32, self.1 as f32]
+        }
+
+        fn transposition_hash(&self) -> Option<u64> {
+            Some((self.0 * 2 + self.1) as u64)
+        }
+    }
+
+    /// bet on a coin landing heads a quarter of the time, winning or losing one, or pass and
+    /// lose a fifth
+    enum Bet {
+        Start,
+        Flip,
+        Done(f32),
+    }
+
+    impl State for Bet {
+        const ACTION_SPACE: usize = 2;
+        const HEIGHT: usize = 1;
+        const WIDTH: usize = 1;
+
+        fn init() -> Self {
+            Bet::Start
+        }
+
+        fn action_count(&self) -> usize {
+            match self {
+                Bet::Done(_) => 0,
+                _ => 2,
+            }
+        }
+
+        fn chance_outcomes(&self) -> Option<Vec<f32>> {
+            matches!(self, Bet::Flip).then(|| vec![0.25, 0.75])
+        }
+
+        fn take(&self, action: Action) -> Self {
+            match (self, action.index()) {
+                (Bet::Start, 0) => Bet::Flip,
+                (Bet::Start, _) => Bet::Done(-0.2),
+                (_, 0) => Bet::Done(1.0),
+                (_, _) => Bet::Done(-1.0),
+            }
+        }
+
+        fn player_count(&self) -> usize {
+            1
+        }
+
+        fn to_play(&self) -> usize {
+            0
+        }
+
+        fn outcome(&self) -> Option<Vec<f32>> {
+            match self {
+                Bet::Done(reward) => Some(vec![*reward]),
+                _ => None,
+            }
+        }
+
+        fn observation(&self) -> Vec<f32> {
+            vec![0.0]
+        }
+    }
+
+    /// settle for the full reward or for three tenths of it
+    struct Settle(Option<f32>);
+
+    impl State for Settle {
+        const ACTION_SPACE: usize = 2;
+        const HEIGHT: usize = 1;
+        const WIDTH: usize = 1;
+
+        fn init() -> Self {
+            Settle(None)
+        }
+
+        fn action_count(&self) -> usize {
+            if self.0.is_some() { 0 } else { 2 }
+        }
+
+        fn take(&self, action: Action) -> Self {
+            Settle(Some([1.0, 0.3][action.index()]))
+        }
+
+        /// names awkward to quote
+        fn action_name(&self, action: Action) -> String {
+            ["take \"all\"\n", "settle\t\u{1}"][action.index()].to_string()
+        }
+
+        fn player_count(&self) -> usize {
+            1
+        }
+
+        fn to_play(&self) -> usize {
+            0
+        }
+
+        fn outcome(&self) -> Option<Vec<f32>> {
+            self.0.map(|reward| vec![reward])
+        }
+
+        fn observation(&self) -> Vec<f32> {
+            vec![self.0.unwrap_or(0.0)]
+        }
+    }
+
+    /// quit losing a fifth, or walk down a binary tree twelve steps deep losing nine tenths
+    enum Descend {
+        Start,
+        Quit,
+        Walk(usize),
+    }
+
+    impl State for Descend {
+        const ACTION_SPACE: usize = 2;
+        const HEIGHT: usize = 1;
+        const WIDTH: usize = 1;
+
+        fn init() -> Self {
+            Descend::Start
+        }
+
+        fn action_count(&self) -> usize {
+            match self {
+                Descend::Quit | Descend::Walk(12) => 0,
+                _ => 2,
+            }
+        }
+
+        fn take(&self, action: Action) -> Self {
+            match (self, action.index()) {
+                (Descend::Start, 0) => Descend::Quit,
+                (Descend::Start, _) => Descend::Walk(1),
+                (Descend::Walk(steps), _) => Descend::Walk(steps + 1),
+                (Descend::Quit, _) => unreachable!("quitting ends the game"),
+            }
+        }
+
+        fn player_count(&self) -> usize {
+            1
+        }
+
+        fn to_play(&self) -> usize {
+            0
+        }
+
+        fn outcome(&self) -> Option<Vec<f32>> {
+            match self {
+                Descend::Quit => Some(vec![-0.2]),
+                Descend::Walk(12) => Some(vec![-0.9]),
+                _ => None,
+            }
+        }
+
+        fn observation(&self) -> Vec<f32> {
+            vec![match self {
+                Descend::Start => 0.0,
+                Descend::Quit => -1.0,
+                Descend::Walk(steps) => *steps as f32,
+            }]
+        }
+    }
+
+    /// bank a sure win in four moves, or roll a die winning unless it lands on one, the rolls
+    /// other than one counting as a single outcome
+    enum Gamble {
+        Start,
+        Bank(usize),
+        Roll,
+        Done(f32),
+    }
+
+    impl State for Gamble {
+        const ACTION_SPACE: usize = 2;
+        const HEIGHT: usize = 1;
+        const WIDTH: usize = 1;
+
+        fn init() -> Self {
+            Gamble::Start
+        }
+
+        fn action_count(&self) -> usize {
+            match self {
+                Gamble::Start => 2,
+                Gamble::Bank(_) => 1,
+                Gamble::Roll => 2,
+                Gamble::Done(_) => 0,
+            }
+        }
+
+        fn chance_outcomes(&self) -> Option<Vec<f32>> {
+            matches!(self, Gamble::Roll).then(|| vec![1.0 / 6.0, 5.0 / 6.0])
+        }
+
+        fn take(&self, action: Action) -> Self {
+            match (self, action.index()) {
+                (Gamble::Start, 0) => Gamble::Bank(1),
+                (Gamble::Bank(steps), _) if *steps < 3 => Gamble::Bank(steps + 1),
+                (Gamble::Start, _) => Gamble::Roll,
+                (Gamble::Roll, 0) => Gamble::Done(-1.0),
+                (_, _) => Gamble::Done(1.0),
+            }
+        }
+
+        fn player_count(&self) -> usize {
+            1
+        }
+
+        fn to_play(&self) -> usize {
+            0
+        }
+
+        fn outcome(&self) -> Option<Vec<f32>> {
+            match self {
+                Gamble::Done(reward) => Some(vec![*reward]),
+                _ => None,
+            }
+        }
+
+        fn observation(&self) -> Vec<f32> {
+            vec![0.0]
+        }
+    }
+
+    /// three players vote in turn, all winning if every vote is for the second option
+    struct Agree(Vec<usize>);
+
+    impl State for Agree {
+        const ACTION_SPACE: usize = 2;
+        const HEIGHT: usize = 1;
+        const WIDTH: usize = 1;
+
+        fn init() -> Self {
+            Agree(Vec::new())
+        }
+
+        fn action_count(&self) -> usize {
+            if self.0.len() < 3 { 2 } else { 0 }
+        }
+
+        fn take(&self, action: Action) -> Self {
+            Agree(self.0.iter().copied().chain([action.index()]).collect())
+        }
+
+        fn player_count(&self) -> usize {
+            3
+        }
+
+        fn to_play(&self) -> usize {
+            self.0.len() % 3
+        }
+
+        fn outcome(&self) -> Option<Vec<f32>> {
+            let reward = if self.0.iter().all(|vote| *vote == 1) { 1.0 } else { 0.0 };
+            (self.0.len() == 3).then(|| vec![reward; 3])
+        }
+
+        /// cooperative, every player sharing the value
+        fn values(&self, value: f32) -> Vec<f32> {
+            vec![value; 3]
+        }
+
+        fn observation(&self) -> Vec<f32> {
+            vec![self.0.len() as f32]
+        }
+    }
+
+    /// three players, the first having to crown the last
+    #[derive(Clone)]
+    struct Crown(bool);
+
+    impl State for Crown {
+        const ACTION_SPACE: usize = 1;
+        const HEIGHT: usize = 1;
+        const WIDTH: usize = 1;
+
+        fn init() -> Self {
+            Crown(false)
+        }
+
+        fn action_count(&self) -> usize {
+            if self.0 { 0 } else { 1 }
+        }
+
+        fn take(&self, _action: Action) -> Self {
+            Crown(true)
+        }
+
+        fn player_count(&self) -> usize {
+            3
+        }
+
+        fn to_play(&self) -> usize {
+            0
+        }
+
+        fn outcome(&self) -> Option<Vec<f32>> {
+            self.0.then(|| vec![-0.5, -0.5, 1.0])
+        }
+
+        fn observation(&self) -> Vec<f32> {
+            vec![self.0 as u8 as f32]
+        }
+    }
+
+    /// count down from three, its outcome only to be asked for once the count is over
+    struct Countdown(usize);
+
+    impl State for Countdown {
+        const ACTION_SPACE: usize = 1;
+        const HEIGHT: usize = 1;
+        const WIDTH: usize = 1;
+
+        fn init() -> Self {
+            Countdown(3)
+        }
+
+        fn action_count(&self) -> usize {
+            self.0.min(1)
+        }
+
+        fn take(&self, _action: Action) -> Self {
+            Countdown(self.0 - 1)
+        }
+
+        fn to_play(&self) -> usize {
+            self.0 % 2
+        }
+
+        fn is_terminal(&self) -> bool {
+            self.0 == 0
+        }
+
+        fn outcome(&self) -> Option<Vec<f32>> {
+            assert!(self.is_terminal(), "outcome of a state still in play");
+            Some(vec![1.0, -1.0])
+        }
+
+        fn observation(&self) -> Vec<f32> {
+            vec![self.0 as f32]
+        }
+    }
+
+    fn first_action() -> Action {
+        Pick::init().action_iter().next().expect("first action")
+    }
+
+    fn fresh_node() -> Node<3, Pick> {
+        let node = Node::new(Pick::init());
+        node.init_actions(ActionMap::new([0.2, 0.3, 0.5].map(F32)), None);
+        node
+    }
+
+    /// node whose first action was taken once with the given value
+    fn visited_node(value: f32) -> Node<3, Pick> {
+        let node = fresh_node();
+        let stats = &node.action_stats()[first_action()];
+        stats.add_virtual_loss();
+        stats.record(value);
+        node.visit_count.fetch_add(1, Relaxed);
+        node
+    }
+
+    /// config selecting on Q(s, a) alone
+    fn config(first_play_urgency: FirstPlayUrgency) -> SearchConfig {
+        SearchConfig::new()
+            .with_c_puct(0.0)
+            .with_first_play_urgency(first_play_urgency)
+    }
+
+    #[test]
+    fn fresh_node_has_no_quality() {
+        let node = fresh_node();
+        assert_eq!(node.quality(), None);
+        assert!(node.action_stats().iter().all(|stats| stats.quality().is_none()));
+    }
+
+    #[test]
+    fn fresh_node_puct_is_not_nan() {
+        let first_play_urgencies = [
+            FirstPlayUrgency::Infinite,
+            FirstPlayUrgency::ParentReduction(0.25),
+            FirstPlayUrgency::Value(0.5),
+            FirstPlayUrgency::Loss,
+            FirstPlayUrgency::Win,
+        ];
+        let node = fresh_node();
+        for first_play_urgency in first_play_urgencies {
+            let config = SearchConfig::new().with_first_play_urgency(first_play_urgency);
+            let quality = first_play_urgency.quality(0.0, -1.0, 1.0);
+            for stats in node.action_stats().iter() {
+                assert!(!stats.puct(node.visit_count(), &config, quality, None).is_nan());
+            }
+            assert!(node.select_action(&config, None).index() < 3);
+        }
+    }
+
+    #[test]
+    fn infinite_urgency_takes_new_actions() {
+        let node = visited_node(1.0);
+        let action = node.select_action(&config(FirstPlayUrgency::Infinite), None);
+        assert_ne!(action, first_action());
+    }
+
+    #[test]
+    fn loss_urgency_takes_visited_actions() {
+        let node = visited_node(0.0);
+        let action = node.select_action(&config(FirstPlayUrgency::Loss), None);
+        assert_eq!(action, first_action());
+    }
+
+    #[test]
+    fn win_urgency_takes_new_actions() {
+        let node = visited_node(0.0);
+        let action = node.select_action(&config(FirstPlayUrgency::Win), None);
+        assert_ne!(action, first_action());
+    }
+
+    #[test]
+    fn value_urgency_compares_with_quality() {
+        let node = visited_node(0.5);
+        let action = node.select_action(&config(FirstPlayUrgency::Value(0.25)), None);
+        assert_eq!(action, first_action());
+        let action = node.select_action(&config(FirstPlayUrgency::Value(0.75)), None);
+        assert_ne!(action, first_action());
+    }
+
+    /// states, bits of the policies and values of a self-play game
+    fn self_play(seed: u64) -> Vec<(u32, Vec<u32>, u32)> {
+        let config = SearchConfig::new().with_sim_count(20).with_seed(Some(seed));
+        let evaluator = evaluator::RolloutEvaluator::new(1, seed);
+        Tree::<2, Nim>::new(config)
+            .execute_episode(&evaluator)
+            .map(|example| {
+                let pi = example.pi.iter().map(|p| p.to_bits()).collect();
+                (example.state[0].to_bits(), pi, example.value.to_bits())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chance_roots_sample_outcomes_by_probability() {
+        let mut tree = Tree::<2, Bet>::with_seed(SearchConfig::new(), 0);
+        tree.simulate(tree.root_ref(), 100, &evaluator::UniformEvaluator);
+        tree.advance(first_action());
+        assert!(tree.root().is_chance());
+        // heads is a proven win, yet lands only a quarter of the time
+        let heads = (0..1000).filter(|_| tree.sample_action(0.0) == first_action()).count();
+        assert!((200..300).contains(&heads), "{heads} heads");
+    }
+
+    #[test]
+    fn rollouts_value_every_player_by_the_outcome() {
+        let evaluator = evaluator::RolloutEvaluator::new(4, 0);
+        let evaluation: Evaluation<1> = evaluator.evaluate(&Crown::init());
+        // the zero-sum split of the mover's -0.5 would give the other two 0.25 each
+        assert_eq!(evaluation.value, vec![-0.5, -0.5, 1.0]);
+    }
+
+    #[test]
+    fn uniform_evaluator_spreads_the_priors_and_values_at_zero() {
+        let evaluation: Evaluation<3> = evaluator::UniformEvaluator.evaluate(&Pick::init());
+        let total = evaluation.priors.iter().map(|prior| prior.0).sum::<f32>();
+        assert!((total - 1.0).abs() < 1e-6);
+        assert!(evaluation.priors.iter().all(|prior| prior.0 == evaluation.priors[first_action()].0));
+        assert_eq!(evaluation.value, vec![0.0; 2]);
+        let mut tree = Tree::<2, Nim>::with_seed(SearchConfig::new().with_noise(None), 0);
+        tree.simulate(tree.root_ref(), 3, &evaluator::UniformEvaluator);
+        // the first simulation expands the root, the others back up the values of its children
+        assert_eq!(tree.root().value(), Some(0.0));
+        assert_eq!(tree.root().visit_count(), 3);
+        assert_eq!(tree.root().quality(), Some(0.0));
+    }
+
+    #[test]
+    fn normalized_q_proves_the_best_score() {
+        let config = SearchConfig::new().with_normalize_q(true).with_noise(None);
+        let mut tree = Tree::<3, Score>::with_seed(config, 0);
+        tree.simulate(tree.root_ref(), 10, &evaluator::UniformEvaluator);
+        // every score is positive, the first one proven is no win
+        assert_eq!(tree.root().proven_value(), Some(20.0));
+        assert_eq!(tree.sample_action(0.0).index(), 1);
+    }
+
+    #[test]
+    fn normalized_q_takes_urgency_from_the_bounds() {
+        let node = fresh_node();
+        let min_max = MinMaxStats::default();
+        [5.0, 20.0].into_iter().for_each(|quality| min_max.update(quality));
+        let loss = node.first_play_urgency(&config(FirstPlayUrgency::Loss), Some(&min_max));
+        let win = node.first_play_urgency(&config(FirstPlayUrgency::Win), Some(&min_max));
+        assert_eq!((loss, win), (Some(5.0), Some(20.0)));
+    }
+
+    #[test]
+    fn rollout_moves_count_as_first() {
+        let config = SearchConfig::new()
+            .with_rave(Some(config::RaveConfig::new()))
+            .with_noise(None);
+        let mut tree = Tree::<2, Nim>::with_seed(config, 0);
+        // the root once expanded and once through one of its actions, both taken in rollouts
+        tree.simulate(tree.root_ref(), 2, &evaluator::RolloutEvaluator::new(20, 0));
+        let counts = tree.root().action_stats().iter().map(Stats::amaf_count).collect_vec();
+        assert_eq!(counts, [1, 1]);
+    }
+
+    #[test]
+    fn terminal_checks_leave_outcomes_to_terminal_states() {
+        let config = SearchConfig::new().with_sim_count(8).with_noise(None);
+        let mut tree = Tree::<1, Countdown>::with_seed(config, 0);
+        let evaluator = evaluator::RolloutEvaluator::new(2, 0);
+        let values = tree.execute_episode(&evaluator).map(|example| example.value).collect_vec();
+        assert_eq!(values, [-1.0, 1.0, -1.0]);
+    }
+
+    #[test]
+    fn parallel_simulations_all_back_up() {
+        for batch_size in [1, 3] {
+            let config = SearchConfig::new().with_threads(4).with_batch_size(batch_size);
+            let mut tree = Tree::<2, Nim>::with_seed(config, 0);
+            tree.simulate_parallel(tree.root_ref(), 200, &evaluator::UniformEvaluator);
+            assert_eq!(tree.root().visit_count(), 200);
+            let virtual_counts = tree
+                .nodes()
+                .iter()
+                .filter(|node| node.has_actions())
+                .flat_map(|node| node.action_stats().iter())
+                .map(|stats| stats.virtual_count.load(Relaxed))
+                .sum::<usize>();
+            assert_eq!(virtual_counts, 0);
+        }
+    }
+
+    #[test]
+    fn parallel_search_proves_the_win() {
+        let config = SearchConfig::new().with_threads(4).with_noise(None);
+        let mut tree = Tree::<2, Nim>::with_seed(config, 0);
+        tree.simulate_parallel(tree.root_ref(), 500, &evaluator::UniformEvaluator);
+        // taking one leaves a multiple of three
+        assert_eq!(tree.proven(), Some(Proven::Win));
+        assert_eq!(tree.sample_action(0.0), first_action());
+    }
+
+    #[test]
+    fn same_seed_plays_identical_games() {
+        assert_eq!(self_play(7), self_play(7));
+        let games = (0..8).map(self_play).collect_vec();
+        assert!(games.iter().any(|game| *game != games[0]));
+    }
+
+    #[test]
+    fn chance_nodes_back_up_expectations() {
+        let mut tree = Tree::<2, Bet>::with_seed(SearchConfig::new(), 0);
+        tree.simulate(tree.root_ref(), 100, &evaluator::UniformEvaluator);
+        let root = tree.root();
+        let bet = &root.action_stats()[first_action()];
+        let flip = &tree.nodes()[bet.child().expect("bet should be searched")];
+        assert!(flip.is_chance());
+        assert_eq!(flip.expected_values(), Some(vec![-0.5]));
+        assert_eq!(bet.proven_value(), Some(-0.5));
+        assert_eq!(root.proven_value(), Some(-0.2));
+    }
+
+    #[test]
+    fn lesser_proven_rewards_leave_the_search_going() {
+        let mut tree = Tree::<2, Settle>::with_seed(SearchConfig::new().with_noise(None), 0);
+        tree.simulate(tree.root_ref(), 10, &evaluator::UniformEvaluator);
+        assert_eq!(tree.root().proven_value(), Some(1.0));
+        assert_eq!(tree.sample_action(0.0).index(), 0);
+    }
+
+    #[test]
+    fn proven_losses_stay_selectable_until_beaten() {
+        let evaluator = evaluator::RolloutEvaluator::new(1, 0);
+        let mut tree = Tree::<2, Descend>::with_seed(SearchConfig::new().with_noise(None), 0);
+        tree.simulate(tree.root_ref(), 2000, &evaluator);
+        let quit = &tree.root().action_stats()[first_action()];
+        assert_eq!(quit.proven_value(), Some(-0.2));
+        assert!(quit.count() > 1000);
+        assert_eq!(tree.sample_action(0.0).index(), 0);
+    }
+
+    #[test]
+    fn proven_chances_leave_the_search_going() {
+        let mut tree = Tree::<2, Gamble>::with_seed(SearchConfig::new().with_noise(None), 0);
+        tree.simulate(tree.root_ref(), 100, &evaluator::UniformEvaluator);
+        let roll_action = Gamble::init().action_iter().nth(1).expect("roll action");
+        let roll = &tree.root().action_stats()[roll_action];
+        // rolling is proven first at its expectation, which is no reason to stop
+        assert!(roll.proven_value().is_some_and(|value| (value - 2.0 / 3.0).abs() < 1e-6));
+        assert_eq!(tree.root().proven_value(), Some(1.0));
+        assert_eq!(tree.sample_action(0.0).index(), 0);
+    }
+
+    #[test]
+    fn players_maximize_their_own_values() {
+        let config = SearchConfig::new().with_c_puct(1.0);
+        let mut tree = Tree::<2, Agree>::with_seed(config, 0);
+        tree.simulate(tree.root_ref(), 200, &evaluator::UniformEvaluator);
+        let variation = tree.analysis().actions[0].variation.clone();
+        let votes = [tree.analysis().actions[0].action].into_iter().chain(variation);
+        assert!(votes.map(|vote| vote.index()).eq([1, 1, 1]));
+        assert_eq!(tree.root().proven_values(), Some([1.0; 3].as_slice()));
+    }
+
+    #[test]
+    fn advance_frees_the_rest_of_the_tree() {
+        let mut tree = Tree::<3, Pick>::with_seed(SearchConfig::new(), 0);
+        tree.simulate(tree.root_ref(), 10, &evaluator::UniformEvaluator);
+        assert_eq!(tree.nodes().len(), 4);
+        tree.advance(first_action());
+        let stats = tree.nodes().arena_stats();
+        assert_eq!((stats.live, stats.slots, stats.free, stats.collected), (1, 1, 0, 3));
+    }
+
+    #[test]
+    fn advance_compacts_and_remaps_the_kept_subtree() {
+        let config = SearchConfig::new().with_noise(None);
+        let mut tree = Tree::<2, Nim>::with_seed(config, 0);
+        tree.simulate(tree.root_ref(), 40, &evaluator::UniformEvaluator);
+        // the least visited subtree is kept, leaving most of the arena free
+        let (action, stats) = tree
+            .root()
+            .action_stats()
+            .action_value_iter()
+            .min_by_key(|(_, stats)| stats.count())
+            .expect("at least one action");
+        let child_ref = stats.child().expect("both actions taken");
+        let visits = tree.nodes[child_ref].visit_count();
+        tree.advance(action);
+        let stats = tree.nodes().arena_stats();
+        assert_eq!((stats.slots, stats.free), (stats.live, 0));
+        assert_eq!(tree.root().visit_count(), visits);
+        tree.simulate(tree.root_ref(), 10, &evaluator::UniformEvaluator);
+        assert_eq!(tree.root().visit_count(), visits + 10);
+        assert_eq!(tree.nodes().iter().count(), tree.nodes().arena_stats().live);
+    }
+
+    #[test]
+    fn node_budget_stops_adding_nodes() {
+        let budget = NodeBudgetConfig::new(2).with_prune_to(None);
+        let config = SearchConfig::new().with_node_budget(Some(budget));
+        let mut tree = Tree::<3, Pick>::with_seed(config, 0);
+        tree.simulate(tree.root_ref(), 10, &evaluator::UniformEvaluator);
+        assert_eq!(tree.nodes().len(), 2);
+        assert_eq!(tree.root().visit_count(), 10);
+    }
+
+    #[test]
+    fn node_budget_prunes_to_make_room() {
+        let budget = NodeBudgetConfig::new(12).with_prune_to(Some(0.75));
+        let config = SearchConfig::new().with_node_budget(Some(budget)).with_batch_size(4);
+        let mut tree = Tree::<2, Nim>::with_seed(config, 0);
+        tree.simulate(tree.root_ref(), 200, &evaluator::UniformEvaluator);
+        let stats = tree.nodes().arena_stats();
+        assert!(stats.live <= 12, "{stats:?}");
+        assert_eq!(stats.live, tree.nodes().iter().count());
+        assert_eq!(stats.slots, stats.live + stats.free);
+        assert!(stats.collected > 0 && stats.reused > 0, "{stats:?}");
+        let best = tree.root().action_stats().iter().max_by_key(|stats| stats.count());
+        assert!(best.and_then(Stats::child).is_some());
+        assert_eq!(tree.root().visit_count(), 200);
+    }
+
+    #[test]
+    fn parent_reduction_urgency_is_below_parent_quality() {
+        let node = visited_node(0.5);
+        assert_eq!(node.quality(), Some(0.5));
+        let action = node.select_action(&config(FirstPlayUrgency::ParentReduction(0.25)), None);
+        assert_eq!(action, first_action());
+        let action = node.select_action(&config(FirstPlayUrgency::ParentReduction(-0.25)), None);
+        assert_ne!(action, first_action());
+    }
+
+    /// Gumbel root search executing the simulations with the uniform evaluator
+    fn gumbel_search<S: State>(
+        tree: &mut Tree<3, S>,
+        gumbel: &config::GumbelConfig,
+    ) -> (Action, ActionMap<F32<3>>) {
+        tree.gumbel_search(gumbel, &mut |tree, node_ref, action, count| {
+            tree.simulate_through(node_ref, action, count, &evaluator::UniformEvaluator)
+        })
+    }
+
+    #[test]
+    fn gumbel_search_takes_a_simulated_root_action() {
+        let gumbel = config::GumbelConfig::new();
+        let config = SearchConfig::new().with_sim_count(8).with_gumbel(Some(gumbel.clone()));
+        let mut tree = Tree::<3, Pick>::with_seed(config, 0);
+        let (action, pi) = gumbel_search(&mut tree, &gumbel);
+        assert!(tree.root().action_stats()[action].child().is_some());
+        // every action of the root is legal
+        assert_eq!(pi.iter().count(), tree.root().state().legal_actions().len());
+        assert!((pi.iter().map(|p| p.0).sum::<f32>() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn gumbel_search_takes_the_best_q_on_a_tiny_budget() {
+        for seed in 0..8 {
+            // two of the three scores considered, leaving the root unproven
+            let gumbel = config::GumbelConfig::new().with_considered_actions(2);
+            let config = SearchConfig::new()
+                .with_sim_count(3)
+                .with_gumbel(Some(gumbel.clone()))
+                .with_normalize_q(true);
+            let mut tree = Tree::<3, Score>::with_seed(config, seed);
+            let (action, _) = gumbel_search(&mut tree, &gumbel);
+            assert_eq!(tree.root().proven_value(), None);
+            let best = tree
+                .root()
+                .action_stats()
+                .action_value_iter()
+                .filter_map(|(action, stats)| Some((action, stats.quality()?)))
+                .max_by_key(|(_, quality)| OrderedFloat(*quality))
+                .expect("visited actions");
+            assert_eq!(action, best.0);
+        }
+    }
+
+    #[test]
+    fn search_stops_once_the_lead_cannot_be_caught() {
+        // every simulation after the first action taken goes to that action
+        let config = config(FirstPlayUrgency::Loss).with_noise(None);
+        let mut tree = Tree::<3, Pick>::with_seed(config, 0);
+        let done = tree.search_with_budget(None, None, Some(100), &evaluator::UniformEvaluator);
+        assert!(done < 100, "{done} simulations");
+        assert!(tree.visit_lead() > 100 - done);
+        // but could still be caught one simulation earlier
+        assert!(tree.visit_lead() - 1 <= 100 - (done - 1));
+        assert_eq!(tree.root().visit_count(), done);
+    }
+
+    #[test]
+    fn move_orders_share_a_node_with_separate_edges() {
+        let config = SearchConfig::new().with_transpositions(true).with_noise(None);
+        let mut tree = Tree::<2, Nim>::with_seed(config, 0);
+        tree.simulate(tree.root_ref(), 100, &evaluator::UniformEvaluator);
+        let [take_one, take_two] =
+            [0, 1].map(|index| Nim::init().action_iter().nth(index).expect("two actions"));
+        let nodes = tree.nodes();
+        let edge = |node_ref, action| &nodes[node_ref].action_stats()[action];
+        let child = |node_ref, action| edge(node_ref, action).child().expect("action taken");
+        // four left for the first player by taking one then two, or two then one
+        let one_then_two = (child(tree.root_ref(), take_one), take_two);
+        let two_then_one = (child(tree.root_ref(), take_two), take_one);
+        let shared_ref = child(one_then_two.0, one_then_two.1);
+        assert_eq!(child(two_then_one.0, two_then_one.1), shared_ref);
+        let counts =
+            [one_then_two, two_then_one].map(|(node_ref, action)| edge(node_ref, action).count());
+        assert!(counts.iter().all(|count| *count > 0), "{counts:?}");
+        assert_eq!(counts[0] + counts[1], nodes[shared_ref].visit_count());
+    }
+
+    #[test]
+    fn principal_variations_follow_the_most_visited_children() {
+        let mut tree = Tree::<2, Nim>::with_seed(SearchConfig::new().with_noise(None), 0);
+        tree.simulate(tree.root_ref(), 100, &evaluator::UniformEvaluator);
+        let analysis = tree.analysis();
+        assert_eq!(analysis.actions.len(), 2);
+        for action_analysis in &analysis.actions {
+            let stats = &tree.root().action_stats()[action_analysis.action];
+            let mut curr_ref = stats.child().expect("action taken");
+            for action in &action_analysis.variation {
+                let action_stats = tree.nodes[curr_ref].action_stats();
+                let most_visits = action_stats.iter().map(Stats::count).max();
+                assert_eq!(Some(action_stats[*action].count()), most_visits);
+                curr_ref = action_stats[*action].child().expect("most visited child");
+            }
+            // down to a state not searched further
+            let curr = &tree.nodes[curr_ref];
+            let unvisited = || curr.action_stats().iter().all(|stats| stats.count() == 0);
+            assert!(!curr.has_actions() || unvisited());
+        }
+    }
+
+    /// deepest depth below the JSON node, each action leading to a node with at least the
+    /// given visits
+    fn json_depth(node: &serde_json::Value, min_visits: u64) -> usize {
+        let children = node["children"].as_array().expect("children array");
+        children
+            .iter()
+            .map(|child| {
+                assert!(child["visits"].as_u64().expect("visits") >= min_visits);
+                1 + json_depth(&child["node"], min_visits)
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn exports_respect_the_limit() {
+        let mut tree = Tree::<2, Nim>::with_seed(SearchConfig::new().with_noise(None), 0);
+        tree.simulate(tree.root_ref(), 100, &evaluator::UniformEvaluator);
+        let unlimited = export::ExportLimit { max_depth: usize::MAX, min_visits: 1 };
+        let deep: serde_json::Value =
+            serde_json::from_str(&tree.to_json(unlimited)).expect("valid JSON");
+        assert_eq!(deep["visits"], 100);
+        assert!(json_depth(&deep, 1) > 2);
+        for (max_depth, min_visits) in [(2, 1), (7, 10), (0, 1)] {
+            let limit = export::ExportLimit { max_depth, min_visits: min_visits as usize };
+            let json: serde_json::Value =
+                serde_json::from_str(&tree.to_json(limit)).expect("valid JSON");
+            assert!(json_depth(&json, min_visits) <= max_depth);
+            // one DOT edge for each exported action
+            let edges = tree.to_dot(limit).matches(" -> ").count();
+            assert_eq!(edges, json.to_string().matches("\"action\"").count());
+        }
+    }
+
+    #[test]
+    fn json_exports_quote_move_names() {
+        let mut tree = Tree::<2, Settle>::with_seed(SearchConfig::new().with_noise(None), 0);
+        tree.simulate(tree.root_ref(), 10, &evaluator::UniformEvaluator);
+        let json = tree.to_json(export::ExportLimit::default());
+        let json: serde_json::Value = serde_json::from_str(&json).expect("valid JSON");
+        let moves = json["children"]
+            .as_array()
+            .expect("children array")
+            .iter()
+            .map(|child| child["move"].as_str().expect("move string"))
+            .collect::<Vec<_>>();
+        assert_eq!(moves, ["take \"all\"\n", "settle\t\u{1}"]);
+    }
+
+    #[test]
+    fn solver_proves_a_one_move_win() {
+        let mut tree = Tree::<2, Nim>::with_seed(SearchConfig::new().with_noise(None), 0);
+        let take_two = Nim::init().action_iter().nth(1).expect("two actions");
+        // down to two left for the second player
+        for action in [take_two, take_two, first_action()] {
+            tree.advance(action);
+        }
+        tree.simulate(tree.root_ref(), 4, &evaluator::UniformEvaluator);
+        // taking both is the win, taking one leaves the opponent the last
+        assert_eq!(tree.proven(), Some(Proven::Win));
+        assert_eq!(tree.root().proven_value(), Some(1.0));
+        assert_eq!(tree.root().select_action(tree.config(), None), take_two);
+    }
+}
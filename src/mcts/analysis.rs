@@ -0,0 +1,138 @@
+use super::{NodeRef, Proven, Tree};
+use crate::env::{Action, State};
+use itertools::Itertools;
+use std::fmt::{Display, Formatter};
+
+/// search statistics of an action of the root
+pub struct ActionAnalysis {
+    pub action: Action,
+    /// N(s, a)
+    pub visits: usize,
+    /// Q(s, a), none until taken
+    pub quality: Option<f32>,
+    /// P(s, a)
+    pub prior: f32,
+    /// PUCT(s, a) the action would be selected by
+    pub puct: f32,
+    pub proven: Option<Proven>,
+    /// principal variation after the action, the most visited action from each state on
+    pub variation: Vec<Action>,
+}
+
+/// search statistics of the root, its actions most visited first
+pub struct Analysis<'a, S: State> {
+    state: &'a S,
+    /// N(s)
+    pub visits: usize,
+    /// Q(s), none before any visit
+    pub quality: Option<f32>,
+    pub proven: Option<Proven>,
+    pub actions: Vec<ActionAnalysis>,
+}
+
+impl<
+    const N: usize,
+    S: State,
+> Tree<N, S> {
+    /// statistics of the search from the root, for inspecting why an action is taken
+    pub fn analysis(&self) -> Analysis<'_, S> {
+        let root = self.root();
+        let actions = match root.has_actions() {
+            false => Vec::new(),
+            true => {
+                let min_max = self.nodes.min_max.as_ref();
+                let first_play_urgency = root.first_play_urgency(&self.config, min_max);
+                root.action_stats()
+                    .action_value_iter()
+                    .map(|(action, stats)| ActionAnalysis {
+                        action,
+                        visits: stats.count(),
+                        quality: stats.quality(),
+                        prior: stats.prior(),
+                        puct: stats.puct(
+                            root.visit_count(),
+                            &self.config,
+                            first_play_urgency,
+                            min_max,
+                        ),
+                        proven: stats.proven(),
+                        variation: stats
+                            .child()
+                            .map_or_else(Vec::new, |child_ref| self.variation(child_ref)),
+                    })
+                    .sorted_by_key(|analysis| std::cmp::Reverse(analysis.visits))
+                    .collect()
+            }
+        };
+        Analysis {
+            state: root.state(),
+            visits: root.visit_count(),
+            quality: root.quality(),
+            proven: root.proven(),
+            actions,
+        }
+    }
+
+    /// most visited action from each state on, down to a state not searched further
+    fn variation(&self, node_ref: NodeRef<N>) -> Vec<Action> {
+        let mut variation = Vec::new();
+        let mut curr_ref = node_ref;
+        while self.nodes[curr_ref].has_actions() {
+            let most_visited = self.nodes[curr_ref]
+                .action_stats()
+                .action_value_iter()
+                .filter(|(_, stats)| stats.count() > 0)
+                .max_by_key(|(_, stats)| stats.count());
+            let Some((action, stats)) = most_visited else {
+                break;
+            };
+            variation.push(action);
+            match stats.child() {
+                Some(child_ref) => curr_ref = child_ref,
+                None => break,
+            }
+        }
+        variation
+    }
+}
+
+impl<S: State> Analysis<'_, S> {
+    /// names of the action and its principal variation, each in the state it is taken from
+    fn line(&self, analysis: &ActionAnalysis) -> String {
+        let mut state = self.state.take(analysis.action);
+        let mut names = vec![self.state.action_name(analysis.action)];
+        for action in &analysis.variation {
+            names.push(state.action_name(*action));
+            state = state.take(*action);
+        }
+        names.join(" ")
+    }
+}
+
+fn format_quality(quality: Option<f32>, proven: Option<Proven>) -> String {
+    match (proven, quality) {
+        (Some(proven), _) => format!("{proven:?}"),
+        (None, Some(quality)) => format!("{quality:.3}"),
+        (None, None) => "-".to_string(),
+    }
+}
+
+impl<S: State> Display for Analysis<'_, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "N {} Q {}", self.visits, format_quality(self.quality, self.proven))?;
+        writeln!(f, "{:>6} {:>7} {:>6} {:>6} {:>7}  PV", "move", "N", "Q", "P", "PUCT")?;
+        for analysis in &self.actions {
+            writeln!(
+                f,
+                "{:>6} {:>7} {:>6} {:>6.3} {:>7.3}  {}",
+                self.state.action_name(analysis.action),
+                analysis.visits,
+                format_quality(analysis.quality, analysis.proven),
+                analysis.prior,
+                analysis.puct,
+                self.line(analysis),
+            )?;
+        }
+        Ok(())
+    }
+}
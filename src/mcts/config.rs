@@ -0,0 +1,183 @@
+use burn::config::Config;
+
+/// search parameters, serialized alongside the `TrainingConfig`
+#[derive(Config, Debug)]
+pub struct SearchConfig {
+    /// number of simulations before each move
+    #[config(default = 100)]
+    pub sim_count: usize,
+    /// exploration constant c of PUCT(s, a), constant unless the log schedule is given
+    #[config(default = 3.0)]
+    pub c_puct: f32,
+    /// AlphaZero log schedule for the exploration constant
+    #[config(default = "None")]
+    pub c_schedule: Option<PuctScheduleConfig>,
+    /// Q(s, a) assumed for actions not yet taken
+    #[config(default = "FirstPlayUrgency::Infinite")]
+    pub first_play_urgency: FirstPlayUrgency,
+    /// temperature for sampling moves and training targets in the opening
+    #[config(default = 1.0)]
+    pub temperature: f32,
+    /// number of opening moves played at `temperature`, none for the whole game
+    #[config(default = "None")]
+    pub temperature_moves: Option<usize>,
+    /// temperature after the opening, zero always takes the most visited action
+    #[config(default = 0.0)]
+    pub final_temperature: f32,
+    /// root noise for self-play, none for evaluation or arena play
+    #[config(default = "Some(NoiseConfig::new())")]
+    pub noise: Option<NoiseConfig>,
+    /// Gumbel AlphaZero root search in `Tree::execute_episode` instead of root noise and
+    /// sampling by visit count, none for PUCT at the root
+    #[config(default = "None")]
+    pub gumbel: Option<GumbelConfig>,
+    /// leaves collected by `Tree::simulate`, or by each worker of `Tree::simulate_parallel`,
+    /// before evaluating them together as one batch
+    #[config(default = 1)]
+    pub batch_size: usize,
+    /// worker threads of `Tree::simulate_parallel`
+    #[config(default = 1)]
+    pub threads: usize,
+    /// value counted as lost for each simulation still descending through an action, steering
+    /// concurrent simulations down different paths
+    #[config(default = 1.0)]
+    pub virtual_loss: f32,
+    /// blend all-moves-as-first values into Q(s, a), none to select on Q(s, a) alone
+    #[config(default = "None")]
+    pub rave: Option<RaveConfig>,
+    /// normalize Q(s, a) to [0, 1] by the minimum and maximum in the tree, for rewards outside
+    /// [-1, 1] such as scores
+    #[config(default = false)]
+    pub normalize_q: bool,
+    /// bound on the nodes in the search arena, none for unbounded
+    #[config(default = "None")]
+    pub node_budget: Option<NodeBudgetConfig>,
+    /// seed of the random number generator of `Tree::new`, none to seed from the OS, making
+    /// the root noise and sampled actions of single-threaded search reproducible
+    #[config(default = "None")]
+    pub seed: Option<u64>,
+    /// share one node between equal states reached by different move orders, keyed by
+    /// `State::transposition_hash`, which requires states never to repeat within a game
+    #[config(default = false)]
+    pub transpositions: bool,
+}
+
+impl SearchConfig {
+    /// c(s): exploration constant for a state visited visit_count times
+    pub fn explore_factor(&self, visit_count: usize) -> f32 {
+        match &self.c_schedule {
+            Some(schedule) => schedule.explore_factor(visit_count),
+            None => self.c_puct,
+        }
+    }
+
+    /// temperature for the move with the given zero based number
+    pub fn temperature(&self, move_number: usize) -> f32 {
+        match self.temperature_moves {
+            Some(moves) if move_number >= moves => self.final_temperature,
+            _ => self.temperature,
+        }
+    }
+}
+
+/// bound on the nodes of the search arena
+#[derive(Config, Debug)]
+pub struct NodeBudgetConfig {
+    /// most nodes in the arena
+    pub max_nodes: usize,
+    /// fraction of max_nodes left after pruning the least visited subtrees once the arena is
+    /// full, none to stop adding nodes instead and evaluate the leaves reached again without
+    /// expanding the tree further
+    #[config(default = "Some(0.75)")]
+    pub prune_to: Option<f32>,
+}
+
+/// first play urgency, Q(s, a) of an action not yet taken when selecting among the actions of s
+#[derive(Config, Debug, Copy, PartialEq)]
+pub enum FirstPlayUrgency {
+    /// take every action once before taking any action again
+    Infinite,
+    /// Q(s) less the reduction, as in Leela Chess Zero
+    ParentReduction(f32),
+    /// the given value
+    Value(f32),
+    /// the reward of a loss, explore the actions taken so far first
+    Loss,
+    /// the reward of a win, explore new actions first
+    Win,
+}
+
+impl FirstPlayUrgency {
+    /// Q(s, a) of an action not yet taken from a state with Q(s) of the given quality, with
+    /// the rewards of a loss and a win, none for infinite
+    pub fn quality(&self, parent_quality: f32, loss: f32, win: f32) -> Option<f32> {
+        match self {
+            FirstPlayUrgency::Infinite => None,
+            FirstPlayUrgency::ParentReduction(reduction) => Some(parent_quality - reduction),
+            FirstPlayUrgency::Value(value) => Some(*value),
+            FirstPlayUrgency::Loss => Some(loss),
+            FirstPlayUrgency::Win => Some(win),
+        }
+    }
+}
+
+/// c(s) = log((1 + N(s) + c_base) / c_base) + c_init
+#[derive(Config, Debug)]
+pub struct PuctScheduleConfig {
+    #[config(default = 19652.0)]
+    pub c_base: f32,
+    #[config(default = 1.25)]
+    pub c_init: f32,
+}
+
+impl PuctScheduleConfig {
+    pub fn explore_factor(&self, visit_count: usize) -> f32 {
+        ((1.0 + visit_count as f32 + self.c_base) / self.c_base).ln() + self.c_init
+    }
+}
+
+/// rapid action value estimation, Q(s, a) blended with the all-moves-as-first value Q~(s, a) as
+/// (1 - beta) * Q(s, a) + beta * Q~(s, a), where beta = sqrt(k / (3 * N(s, a) + k))
+#[derive(Config, Debug)]
+pub struct RaveConfig {
+    /// equivalence parameter k, the visits at which both values weigh about the same
+    #[config(default = 1000.0)]
+    pub equivalence: f32,
+}
+
+impl RaveConfig {
+    pub fn beta(&self, count: usize) -> f32 {
+        (self.equivalence / (3.0 * count as f32 + self.equivalence)).sqrt()
+    }
+}
+
+/// Gumbel-top-k sampling of the root actions with sequential halving, scoring each action as
+/// g(a) + logits(a) + sigma(q(a)) where sigma(q) = (c_visit + max_b N(s, b)) * c_scale * q
+#[derive(Config, Debug)]
+pub struct GumbelConfig {
+    /// number of actions sampled without replacement to halve down to the one taken
+    #[config(default = 16)]
+    pub considered_actions: usize,
+    #[config(default = 50.0)]
+    pub c_visit: f32,
+    #[config(default = 0.1)]
+    pub c_scale: f32,
+}
+
+impl GumbelConfig {
+    /// sigma(q): monotone transformation of a Q value to the scale of the logits
+    pub fn sigma(&self, q: f32, max_visit_count: usize) -> f32 {
+        (self.c_visit + max_visit_count as f32) * self.c_scale * q
+    }
+}
+
+/// Dirichlet noise mixed into the root priors during self-play
+#[derive(Config, Debug)]
+pub struct NoiseConfig {
+    /// concentration of the Dirichlet distribution, AlphaZero uses ~10 / typical action count
+    #[config(default = 0.3)]
+    pub alpha: f32,
+    /// weight of the noise in the mixed prior, (1 - epsilon) * P(s, a) + epsilon * noise
+    #[config(default = 0.25)]
+    pub epsilon: f32,
+}
@@ -0,0 +1,153 @@
+use crate::env::{Action, ActionMap, State};
+use crate::mcts::{sample, F32};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// result of evaluating a state
+pub struct Evaluation<const N: usize> {
+    /// P(s, a): prior probability of each action of the state
+    pub priors: ActionMap<F32<N>>,
+    /// V(s): value of the state for every player, same convention as `State::outcome`
+    pub value: Vec<f32>,
+    /// `State::action_id` of the moves each player made in rollouts from the state, by player,
+    /// counting toward the all-moves-as-first values of RAVE, empty without rollouts
+    pub action_ids: Vec<HashSet<usize>>,
+}
+
+/// evaluates leaf states of the search tree, e.g. with a neural net
+pub trait Evaluator<
+    const N: usize,
+    S: State,
+> {
+    /// priors for the actions of the state and value of the state
+    fn evaluate(&self, state: &S) -> Evaluation<N>;
+
+    /// evaluations of several states, in order, e.g. as one batch through a neural net
+    fn evaluate_batch(&self, states: &[&S]) -> Vec<Evaluation<N>> {
+        states.iter().map(|state| self.evaluate(state)).collect()
+    }
+}
+
+/// uniform priors and zero value, search without any knowledge of the game
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UniformEvaluator;
+
+impl<
+    const N: usize,
+    S: State,
+> Evaluator<N, S> for UniformEvaluator {
+    fn evaluate(&self, state: &S) -> Evaluation<N> {
+        Evaluation {
+            priors: uniform_priors(state),
+            value: vec![0.0; state.player_count()],
+            action_ids: Vec::new(),
+        }
+    }
+}
+
+fn uniform_priors<const N: usize, S: State>(state: &S) -> ActionMap<F32<N>> {
+    let prior = 1.0 / state.action_count() as f32;
+    ActionMap::new(state.action_iter().map(|_action| F32(prior)))
+}
+
+/// chooses the moves of a rollout
+pub trait RolloutPolicy<S: State> {
+    fn choose(&self, state: &S, rng: &mut StdRng) -> Action;
+}
+
+/// uniformly random moves
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RandomPolicy;
+
+impl<S: State> RolloutPolicy<S> for RandomPolicy {
+    fn choose(&self, state: &S, rng: &mut StdRng) -> Action {
+        state
+            .action_iter()
+            .nth(rng.random_range(0..state.action_count()))
+            .expect("non-terminal state should have an action")
+    }
+}
+
+/// uniform priors and the mean outcome of playing rollout_count games out from the state with the
+/// rollout policy, classic UCT when there is no trained model
+pub struct RolloutEvaluator<P = RandomPolicy> {
+    rollout_count: usize,
+    policy: P,
+    /// seeds the generator of each evaluation, locked only to draw the seed so concurrent
+    /// evaluations roll out in parallel
+    rng: Mutex<StdRng>,
+}
+
+impl RolloutEvaluator {
+    /// uniformly random rollouts
+    pub fn new(rollout_count: usize, seed: u64) -> Self {
+        Self::with_policy(rollout_count, seed, RandomPolicy)
+    }
+}
+
+impl<P> RolloutEvaluator<P> {
+    /// rollouts choosing moves with the given policy, e.g. a heuristic for the game
+    pub fn with_policy(rollout_count: usize, seed: u64, policy: P) -> Self {
+        Self {
+            rollout_count: rollout_count.max(1),
+            policy,
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    /// rewards of every player from playing the state out to the end of the game, adding the
+    /// ids of the moves of each player
+    fn rollout<S: State>(
+        &self,
+        state: &S,
+        rng: &mut StdRng,
+        action_ids: &mut [HashSet<usize>],
+    ) -> Vec<f32>
+    where
+        P: RolloutPolicy<S>,
+    {
+        let mut next = None;
+        loop {
+            let state = next.as_ref().unwrap_or(state);
+            if state.is_terminal() {
+                return state.outcome().expect("terminal state should have an outcome");
+            }
+            let action = match state.chance_outcomes() {
+                Some(probabilities) => sample(state.action_iter().zip(probabilities), rng),
+                None => {
+                    let action = self.policy.choose(state, rng);
+                    action_ids[state.to_play()].insert(state.action_id(action));
+                    action
+                }
+            };
+            next = Some(state.take(action));
+        }
+    }
+}
+
+impl<
+    const N: usize,
+    S: State,
+    P: RolloutPolicy<S>,
+> Evaluator<N, S> for RolloutEvaluator<P> {
+    fn evaluate(&self, state: &S) -> Evaluation<N> {
+        let seed = self.rng.lock().expect("rng lock should not be poisoned").random();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut value = vec![0.0; state.player_count()];
+        let mut action_ids = vec![HashSet::new(); state.player_count()];
+        for _ in 0..self.rollout_count {
+            let outcome = self.rollout(state, &mut rng, &mut action_ids);
+            for (total, reward) in value.iter_mut().zip(outcome) {
+                *total += reward;
+            }
+        }
+        value.iter_mut().for_each(|total| *total /= self.rollout_count as f32);
+        Evaluation {
+            priors: uniform_priors(state),
+            value,
+            action_ids,
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use super::{Node, NodeRef, Stats, Tree};
+use crate::env::{Action, State};
+use std::fmt::Write;
+
+/// how far an export walks down from the root, unfolding nodes shared by transpositions into
+/// one copy for each path
+#[derive(Debug, Clone, Copy)]
+pub struct ExportLimit {
+    /// depth of the deepest nodes exported, the root being at depth zero
+    pub max_depth: usize,
+    /// visits an action needs for the node it leads to to be exported
+    pub min_visits: usize,
+}
+
+impl Default for ExportLimit {
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            min_visits: 1,
+        }
+    }
+}
+
+impl<
+    const N: usize,
+    S: State,
+> Tree<N, S> {
+    /// Graphviz DOT of the tree from the root, labelling each node with N(s), Q(s) and the
+    /// reward of a terminal state, and each action leading to it with its move, N(s, a),
+    /// Q(s, a) and P(s, a)
+    pub fn to_dot(&self, limit: ExportLimit) -> String {
+        let mut dot = String::from("digraph tree {\n    node [shape=box, fontname=monospace];\n");
+        let mut next_id = 0;
+        self.dot_node(&mut dot, &mut next_id, self.root_ref, 0, limit);
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// write the node and the subtree below it, returning its id
+    fn dot_node(
+        &self,
+        dot: &mut String,
+        next_id: &mut usize,
+        node_ref: NodeRef<N>,
+        depth: usize,
+        limit: ExportLimit,
+    ) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        let node = &self.nodes[node_ref];
+        let mut label = format!("N={}\\nQ={}", node.visit_count(), format_option(node.quality()));
+        if let Some(reward) = node.reward() {
+            _ = write!(label, "\\nreward={reward:.3}");
+        }
+        _ = writeln!(dot, "    n{id} [label=\"{label}\"];");
+        for (action, stats, child_ref) in self.exported_children(node, depth, limit) {
+            let child_id = self.dot_node(dot, next_id, child_ref, depth + 1, limit);
+            _ = writeln!(
+                dot,
+                "    n{id} -> n{child_id} [label=\"{}\\nN={}\\nQ={}\\nP={:.3}\"];",
+                escape(&node.state().action_name(action)),
+                stats.count(),
+                format_option(stats.quality()),
+                stats.prior(),
+            );
+        }
+        id
+    }
+
+    /// JSON tree from the root, each node with its visits, quality, reward and proven value and
+    /// its children by the actions leading to them with their move, visits, quality and prior
+    pub fn to_json(&self, limit: ExportLimit) -> String {
+        let mut json = String::new();
+        self.json_node(&mut json, self.root_ref, 0, limit);
+        json
+    }
+
+    fn json_node(&self, json: &mut String, node_ref: NodeRef<N>, depth: usize, limit: ExportLimit) {
+        let node = &self.nodes[node_ref];
+        _ = write!(
+            json,
+            "{{\"visits\":{},\"quality\":{},\"reward\":{},\"proven\":{},\"children\":[",
+            node.visit_count(),
+            json_option(node.quality()),
+            json_option(node.reward()),
+            json_option(node.proven_value()),
+        );
+        for (i, (action, stats, child_ref)) in
+            self.exported_children(node, depth, limit).enumerate()
+        {
+            if i > 0 {
+                json.push(',');
+            }
+            _ = write!(
+                json,
+                "{{\"action\":{},\"move\":\"{}\",\"visits\":{},\"quality\":{},\"prior\":{},\"node\":",
+                action.index(),
+                json_escape(&node.state().action_name(action)),
+                stats.count(),
+                json_option(stats.quality()),
+                json_option(Some(stats.prior())),
+            );
+            self.json_node(json, child_ref, depth + 1, limit);
+            json.push('}');
+        }
+        json.push_str("]}");
+    }
+
+    /// actions of the node leading to children within the limit
+    fn exported_children<'a>(
+        &'a self,
+        node: &'a Node<N, S>,
+        depth: usize,
+        limit: ExportLimit,
+    ) -> impl Iterator<Item = (Action, &'a Stats<N>, NodeRef<N>)> + 'a {
+        node.action_stats
+            .get()
+            .filter(|_| depth < limit.max_depth)
+            .into_iter()
+            .flat_map(|action_stats| action_stats.action_value_iter())
+            .filter(move |(_, stats)| stats.count() >= limit.min_visits.max(1))
+            .filter_map(|(action, stats)| Some((action, stats, stats.child()?)))
+    }
+}
+
+fn format_option(value: Option<f32>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| format!("{value:.3}"))
+}
+
+fn json_option(value: Option<f32>) -> String {
+    match value {
+        Some(value) if value.is_finite() => value.to_string(),
+        _ => "null".to_string(),
+    }
+}
+
+/// escape a string for a quoted DOT label
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// escape a string for a JSON string, control characters included
+fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => _ = write!(escaped, "\\u{:04x}", c as u32),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
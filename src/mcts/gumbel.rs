@@ -0,0 +1,143 @@
+use super::config::GumbelConfig;
+use super::{NodeRef, Tree, F32};
+use crate::env::{Action, ActionMap, State};
+use itertools::Itertools;
+use ordered_float::OrderedFloat;
+use rand_distr::{Distribution, Gumbel};
+
+impl<
+    const N: usize,
+    S: State,
+> Tree<N, S> {
+    /// Gumbel AlphaZero root search: sample the considered actions without replacement by
+    /// Gumbel-top-k, spend the simulations on them by sequential halving, and take the best
+    /// remaining one, returning it with the improved policy from the completed Q values as the
+    /// training target
+    pub(super) fn gumbel_search(
+        &mut self,
+        gumbel: &GumbelConfig,
+        search: &mut impl FnMut(&mut Self, NodeRef<N>, Option<Action>, usize),
+    ) -> (Action, ActionMap<F32<N>>) {
+        let root_ref = self.root_ref;
+        let mut sim_count = self.config.sim_count.max(1);
+        if !self.nodes.is_open(root_ref) {
+            // expand the root so it has priors to sample from
+            search(self, root_ref, None, 1);
+            sim_count -= 1;
+        }
+        let distribution = Gumbel::new(0.0, 1.0).expect("unit Gumbel distribution");
+        let gumbel_noise = self.nodes[root_ref]
+            .action_stats()
+            .iter()
+            .map(|_| distribution.sample(&mut self.rng))
+            .collect_vec();
+        let logits = self.logits();
+        let mut considered = (0..logits.len())
+            .sorted_by_key(|a| OrderedFloat(-(gumbel_noise[*a] + logits[*a])))
+            .take(gumbel.considered_actions.max(1))
+            .collect_vec();
+        // sequential halving, simulating each remaining action equally in every phase before
+        // keeping the better half
+        let phase_count = considered.len().next_power_of_two().trailing_zeros() as usize;
+        while considered.len() > 1 {
+            let count = (sim_count / (phase_count * considered.len())).max(1);
+            for a in &considered {
+                let action = self.action(*a);
+                search(self, root_ref, Some(action), count);
+            }
+            let scores = self.scores(gumbel, &gumbel_noise, &logits);
+            considered.sort_by_key(|a| OrderedFloat(-scores[*a]));
+            considered.truncate(considered.len().div_ceil(2));
+        }
+        let root = &self.nodes[root_ref];
+        let action = root
+            .winning_action()
+            .unwrap_or_else(|| self.action(considered[0]));
+        (action, self.improved_policy(gumbel, &logits))
+    }
+
+    /// logits(a) of the root priors
+    fn logits(&self) -> Vec<f32> {
+        self.root()
+            .action_stats()
+            .iter()
+            .map(|stats| stats.prior().max(f32::MIN_POSITIVE).ln())
+            .collect()
+    }
+
+    /// action with the given index among the root actions
+    fn action(&self, index: usize) -> Action {
+        self.root()
+            .action_stats()
+            .action_value_iter()
+            .nth(index)
+            .expect("index should be of a root action")
+            .0
+    }
+
+    /// g(a) + logits(a) + sigma(q(a)) of each root action
+    fn scores(&self, gumbel: &GumbelConfig, gumbel_noise: &[f32], logits: &[f32]) -> Vec<f32> {
+        let max_visit_count = self.max_visit_count();
+        self.completed_q()
+            .into_iter()
+            .zip(gumbel_noise.iter().zip(logits))
+            .map(|(q, (g, logit))| g + logit + gumbel.sigma(q, max_visit_count))
+            .collect()
+    }
+
+    fn max_visit_count(&self) -> usize {
+        self.root()
+            .action_stats()
+            .iter()
+            .map(|stats| stats.count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Q(s, a) of each visited root action, and the mixed value estimate of the root for the
+    /// others, v_mix = (V(s) + sum_b N(s, b) * sum_visited pi(a) * Q(s, a) / sum_visited pi(a))
+    /// / (1 + sum_b N(s, b)), rescaled to [0, 1] by their minimum and maximum
+    fn completed_q(&self) -> Vec<f32> {
+        let root = self.root();
+        let action_stats = root.action_stats();
+        let total_count = action_stats.iter().map(|stats| stats.count()).sum::<usize>();
+        let (visited_prior, visited_q) = action_stats
+            .iter()
+            .filter_map(|stats| Some((stats.prior(), stats.quality()?)))
+            .fold((0.0, 0.0), |(prior, q), (stats_prior, stats_q)| {
+                (prior + stats_prior, q + stats_prior * stats_q)
+            });
+        let value = root.value().unwrap_or(0.0);
+        let mixed_value = if visited_prior > 0.0 {
+            (value + total_count as f32 * visited_q / visited_prior) / (1.0 + total_count as f32)
+        } else {
+            value
+        };
+        let completed_q = action_stats
+            .iter()
+            .map(|stats| stats.quality().unwrap_or(mixed_value))
+            .collect_vec();
+        let (min, max) = completed_q
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), q| (min.min(*q), max.max(*q)));
+        completed_q
+            .into_iter()
+            .map(|q| (q - min) / (max - min).max(f32::EPSILON))
+            .collect()
+    }
+
+    /// pi'(a) = softmax(logits(a) + sigma(completed Q(s, a)))
+    fn improved_policy(&self, gumbel: &GumbelConfig, logits: &[f32]) -> ActionMap<F32<N>> {
+        let max_visit_count = self.max_visit_count();
+        let scores = self
+            .completed_q()
+            .into_iter()
+            .zip(logits)
+            .map(|(q, logit)| logit + gumbel.sigma(q, max_visit_count))
+            .collect_vec();
+        let max_score = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let weights = scores.iter().map(|score| (score - max_score).exp()).collect_vec();
+        let total = weights.iter().sum::<f32>();
+        ActionMap::new(weights.into_iter().map(|weight| F32(weight / total)))
+    }
+}
@@ -1,4 +1,5 @@
 pub mod data;
+pub mod evaluator;
 pub mod model;
 
 pub mod training;
\ No newline at end of file
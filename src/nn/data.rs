@@ -1,49 +1,57 @@
 use burn::data::dataloader::batcher::Batcher;
-use burn::prelude::{Backend, Tensor};
+use burn::prelude::{Backend, Bool, Tensor};
 use burn::tensor::TensorData;
-use itertools::{repeat_n, Itertools};
+use itertools::Itertools;
+use crate::env::State;
 use crate::mcts::Example;
 
 #[derive(Clone)]
 pub struct RLSearchBatcher<B: Backend> {
     device: B::Device,
+    /// channels x height x width of the observations
+    shape: [usize; 3],
 }
 
 impl<B: Backend> RLSearchBatcher<B> {
-    pub fn new(device: B::Device) -> Self {
-        Self { device }
+    /// batcher of the examples of states S, shaped by their observations
+    pub fn new<S: State>(device: B::Device) -> Self {
+        Self { device, shape: [S::CHANNELS, S::HEIGHT, S::WIDTH] }
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct RLSearchBatch<B: Backend> {
-    pub states: Tensor<B, 3>,   // batch_size x height x width
-    pub pis: Tensor<B, 1>, // batch_size x action_count
+    pub states: Tensor<B, 4>,   // batch_size x channels x height x width
+    pub pis: Tensor<B, 2>, // batch_size x action_space
+    pub legal: Tensor<B, 2, Bool>, // batch_size x action_space
     pub values: Tensor<B, 1>,   // batch_size x 1
 }
 
 
-impl<B: Backend, const N: usize, const D: usize> Batcher<Example<N, D>, RLSearchBatch<B>> for RLSearchBatcher<B> {
-    fn batch(&self, examples: Vec<Example<N, D>>) -> RLSearchBatch<B> {
-       let states = examples.iter().map(|example| TensorData::from(example.state))
-           .map(|data| Tensor::<B, 2>::from_data(data, &self.device))
-           .map(|tensor| tensor.reshape([1, D, D]))
+impl<B: Backend, const N: usize> Batcher<Example<N>, RLSearchBatch<B>> for RLSearchBatcher<B> {
+    fn batch(&self, examples: Vec<Example<N>>) -> RLSearchBatch<B> {
+       let [channels, height, width] = self.shape;
+       let states = examples.iter()
+           .inspect(|example| debug_assert_eq!(
+               example.state.len(),
+               channels * height * width,
+               "observation should have the declared shape",
+           ))
+           .map(|example| TensorData::new(example.state.clone(), [1, channels, height, width]))
+           .map(|data| Tensor::<B, 4>::from_data(data, &self.device))
            .collect_vec();
        let states = Tensor::cat(states, 0).to_device(&self.device);
 
-        let pis = examples.iter().map(|example| {
-            let pi_count = example.pi.len();
-            let pis = repeat_n(0.0, pi_count-N.max(pi_count))
-                .chain(example.pi.iter()
-                    .map(|f| f.0))
-                .collect_array::<N>()
-                .unwrap();
-            TensorData::from(pis)
-        })
-            .map(|data| Tensor::<B, 1>::from_data(data, &self.device))
+        let pis = examples.iter().map(|example| TensorData::from(example.pi))
+            .map(|data| Tensor::<B, 1>::from_data(data, &self.device).reshape([1, N]))
             .collect_vec();
         let pis = Tensor::cat(pis, 0).to_device(&self.device);
 
+        let legal = examples.iter().map(|example| TensorData::from(example.legal))
+            .map(|data| Tensor::<B, 1, Bool>::from_data(data, &self.device).reshape([1, N]))
+            .collect_vec();
+        let legal = Tensor::cat(legal, 0).to_device(&self.device);
+
         let values = examples.iter().map(|example| TensorData::from([example.value]))
             .map(|data| Tensor::<B, 1>::from_data(data, &self.device))
             .collect_vec();
@@ -51,6 +59,7 @@ impl<B: Backend, const N: usize, const D: usize> Batcher<Example<N, D>, RLSearch
         RLSearchBatch {
             states,
             pis,
+            legal,
             values,
         }
     }
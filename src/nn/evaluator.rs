@@ -0,0 +1,92 @@
+use crate::env::{ActionMap, State};
+use crate::mcts::evaluator::{Evaluation, Evaluator};
+use crate::mcts::F32;
+use crate::nn::model::Model;
+use burn::prelude::{Backend, Tensor};
+use burn::tensor::TensorData;
+use itertools::Itertools;
+
+/// evaluates states with the policy and value heads of the model
+pub struct ModelEvaluator<B: Backend> {
+    model: Model<B>,
+    device: B::Device,
+}
+
+impl<B: Backend> ModelEvaluator<B> {
+    pub fn new(model: Model<B>, device: B::Device) -> Self {
+        Self { model, device }
+    }
+
+    pub fn model(&self) -> &Model<B> {
+        &self.model
+    }
+}
+
+impl<B: Backend, const N: usize, S: State> Evaluator<N, S>
+    for ModelEvaluator<B>
+{
+    fn evaluate(&self, state: &S) -> Evaluation<N> {
+        self.evaluate_batch(&[state])
+            .pop()
+            .expect("one evaluation for one state")
+    }
+
+    /// one forward pass of the model over a [K, C, H, W] batch of the observations of the states
+    fn evaluate_batch(&self, states: &[&S]) -> Vec<Evaluation<N>> {
+        if states.is_empty() {
+            return Vec::new();
+        }
+        let inputs = states
+            .iter()
+            .map(|state| {
+                let observation = state.observation();
+                debug_assert_eq!(
+                    observation.len(),
+                    S::CHANNELS * S::HEIGHT * S::WIDTH,
+                    "observation should have the declared shape",
+                );
+                TensorData::new(observation, [1, S::CHANNELS, S::HEIGHT, S::WIDTH])
+            })
+            .map(|data| Tensor::<B, 4>::from_data(data, &self.device))
+            .collect_vec();
+        let (pis, values) = self.model.forward(Tensor::cat(inputs, 0));
+        let pis = pis
+            .into_data()
+            .convert::<f32>()
+            .to_vec::<f32>()
+            .expect("policy should convert to f32");
+        let values = values
+            .into_data()
+            .convert::<f32>()
+            .to_vec::<f32>()
+            .expect("value should convert to f32");
+        let action_size = pis.len() / states.len();
+        states
+            .iter()
+            .zip(pis.chunks(action_size))
+            .zip(values)
+            .map(|((state, pi), value)| Evaluation {
+                priors: priors(*state, pi),
+                value: state.values(value),
+                action_ids: Vec::new(),
+            })
+            .collect()
+    }
+}
+
+/// policy head covers the action space, keep and renormalize the probabilities of the legal
+/// actions of the state
+fn priors<const N: usize, S: State>(state: &S, pi: &[f32]) -> ActionMap<F32<N>> {
+    let action_count = state.action_count();
+    let total = pi
+        .iter()
+        .zip(state.legal_actions())
+        .filter_map(|(p, legal)| legal.then_some(p))
+        .sum::<f32>();
+    ActionMap::new(state.action_iter().map(|action| {
+        F32(match pi.get(state.action_id(action)) {
+            Some(p) if total > 0.0 => p / total,
+            _ => 1.0 / action_count as f32,
+        })
+    }))
+}
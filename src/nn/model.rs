@@ -1,10 +1,10 @@
 use burn::tensor::activation::{softmax, tanh};
 use burn::{
     nn::{
-        conv::{Conv1d, Conv1dConfig}
+        conv::{Conv2d, Conv2dConfig}
         ,
         BatchNorm, BatchNormConfig, Dropout, DropoutConfig, Linear,
-        LinearConfig, PaddingConfig1d, Relu
+        LinearConfig, PaddingConfig2d, Relu
     },
     prelude::*,
 };
@@ -32,7 +32,7 @@ use burn::{
 #[derive(Module, Debug)]
 pub struct Model<B: Backend> {
     // tangled
-    conv1: Conv1d<B>,
+    conv1: Conv2d<B>,
     batch_norm1: BatchNorm<B, 2>,
     fc1: Linear<B>,
     batch_norm2: BatchNorm<B, 1>,
@@ -47,7 +47,11 @@ pub struct Model<B: Backend> {
 pub struct ModelConfig {
     board_x: usize,
     board_y: usize,
+    /// planes of the observations, `State::CHANNELS`
+    input_channels: usize,
     num_channels: usize,
+    /// size of the action space `State::ACTION_SPACE`, the policy head indexed by
+    /// `State::action_id`
     action_size: usize,
     #[config(default = "0.5")]
     dropout: f64,
@@ -59,11 +63,11 @@ impl ModelConfig {
     pub fn init<B: Backend>(&self, device: &B::Device) -> Model<B> {
         Model {
             // Conv1D(args.num_channels, 3, padding=‘same’, use_bias=False)(self.input_layer)
-            conv1: Conv1dConfig::new(1, self.num_channels, 3) // ✅ Kernel size fixed
-                .with_padding(PaddingConfig1d::Same) // ✅ Same padding
+            conv1: Conv2dConfig::new([self.input_channels, self.num_channels], [3, 3])
+                .with_padding(PaddingConfig2d::Same) // ✅ Same padding
                 .with_bias(false)// ✅ Matches Keras
                 .init(device),
-            batch_norm1: BatchNormConfig::new(self.num_channels).init(device), // Conv1D → D = 2
+            batch_norm1: BatchNormConfig::new(self.num_channels).init(device), // Conv2D → D = 2
             fc1: LinearConfig::new(self.board_x * self.board_y * self.num_channels, 16).init(device),
             batch_norm2: BatchNormConfig::new(16).init(device), // Dense → D = 1
             fc2: LinearConfig::new(16, 16).init(device),
@@ -76,20 +80,23 @@ impl ModelConfig {
 }
 
 impl<B: Backend> Model<B> {
-    pub fn forward(&self, x: Tensor<B, 3, Int>) -> (Tensor<B, 2>, Tensor<B, 2>) {
-        let x = self.conv1.forward(x.float());
+    /// policy over the action space and value of a [batch_size, channels, height, width] batch
+    /// of observations
+    pub fn forward(&self, x: Tensor<B, 4>) -> (Tensor<B, 2>, Tensor<B, 2>) {
+        let x = self.conv1.forward(x);
         let x = self.batch_norm1.forward(x);
         let x = Relu::new().forward(x); // ✅ Explicit ReLU activation
 
-        let x = x.flatten(1, 2); // ✅ Correct flatten with start & end dim
+        let x = x.flatten::<2>(1, 3); // ✅ Correct flatten with start & end dim
 
         let x = self.fc1.forward(x);
-        let x = self.batch_norm2.forward(x);
+        // batch norm over the features as channels, [batch_size, 16] → [batch_size, 16, 1]
+        let x = self.batch_norm2.forward(x.unsqueeze_dim::<3>(2)).squeeze::<2>(2);
         let x = Relu::new().forward(x); // ✅ Explicit ReLU activation
         let x = self.dropout.forward(x);
 
         let x = self.fc2.forward(x);
-        let x = self.batch_norm3.forward(x);
+        let x = self.batch_norm3.forward(x.unsqueeze_dim::<3>(2)).squeeze::<2>(2);
         let x = Relu::new().forward(x); // ✅ Explicit ReLU activation
         let x = self.dropout.forward(x);
 
@@ -126,8 +133,15 @@ impl<B: Backend> Model<B> {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use burn::backend::NdArray;
+
     #[test]
     fn test_model() {
-
+        let device = Default::default();
+        let model = ModelConfig::new(3, 3, 3, 4, 9, 1e-3).init::<NdArray>(&device);
+        let (pi, v) = model.forward(Tensor::zeros([2, 3, 3, 3], &device));
+        assert_eq!(pi.dims(), [2, 9]);
+        assert_eq!(v.dims(), [2, 1]);
     }
 }
\ No newline at end of file
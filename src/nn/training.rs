@@ -1,11 +1,11 @@
+use crate::nn::data::RLSearchBatch;
 use crate::nn::model::{Model, ModelConfig};
 use burn::nn::loss::Reduction::Mean;
-use burn::nn::loss::{CrossEntropyLossConfig, MseLoss};
+use burn::nn::loss::MseLoss;
 use burn::optim::{Adam, AdamConfig, GradientsParams, Optimizer};
 use burn::optim::adaptor::OptimizerAdaptor;
-use burn::prelude::{Backend, Config, Int, Tensor};
+use burn::prelude::{Backend, Bool, Config, Tensor};
 use burn::tensor::backend::AutodiffBackend;
-use burn::tensor::ElementConversion;
 
 #[derive(Config)]
 pub struct TrainingConfig {
@@ -24,22 +24,21 @@ pub struct TrainingConfig {
 }
 
 impl<B: AutodiffBackend> Model<B> {
+    /// one optimizer step on the batch, the policy learning the search policy pis over the
+    /// legal actions and the value learning the game outcomes
     pub fn train_step(
         self,
-        states: Tensor<B, 3, Int>,
-        target_actions: Tensor<B, 1, Int>,
-        target_values: Tensor<B, 2>,
+        batch: RLSearchBatch<B>,
         optimizer: &mut OptimizerAdaptor<Adam, Model<B>, B>,
         learning_rate: f64,
     ) -> Self {
         // need to seed backend somewhere, probably not here
         // B::seed(config.seed);
-        let (predicted_actions, predicted_values) = self.forward(states);
-        let loss1 = CrossEntropyLossConfig::new()
-            .init(&predicted_actions.device())
-            .forward(predicted_actions.clone(), target_actions.clone());
+        let [batch_size] = batch.values.dims();
+        let (predicted_pis, predicted_values) = self.forward(batch.states);
+        let loss1 = policy_loss(predicted_pis, batch.pis, batch.legal);
         let mse = MseLoss::new();
-        let loss2 = mse.forward(predicted_values, target_values, Mean);
+        let loss2 = mse.forward(predicted_values, batch.values.reshape([batch_size, 1]), Mean);
         let loss = loss1 + loss2;
 
         // Gradients for the current backward pass
@@ -50,3 +49,17 @@ impl<B: AutodiffBackend> Model<B> {
         optimizer.step(learning_rate, self, grads)
     }
 }
+
+/// cross entropy of the predicted policy against the soft targets of the search policy, with the
+/// probabilities of the illegal actions masked out and the rest renormalized, as a softmax over
+/// the legal logits alone
+fn policy_loss<B: Backend>(
+    predicted_pis: Tensor<B, 2>,
+    target_pis: Tensor<B, 2>,
+    legal: Tensor<B, 2, Bool>,
+) -> Tensor<B, 1> {
+    let legal_pis = predicted_pis.mask_fill(legal.bool_not(), 0.0);
+    let total = legal_pis.clone().sum_dim(1).clamp_min(f32::MIN_POSITIVE);
+    let log_pis = (legal_pis / total).clamp_min(f32::MIN_POSITIVE).log();
+    (target_pis * log_pis).sum_dim(1).neg().mean()
+}